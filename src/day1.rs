@@ -14,11 +14,11 @@ fn parse(input: &str) -> impl Iterator<Item = i32> + '_ {
 }
 
 fn solve_part_one(input: &str) -> i32 {
-    parse(input).into_iter().map(calc_fuel).sum()
+    parse(input).map(calc_fuel).sum()
 }
 
 fn solve_part_two(input: &str) -> i32 {
-    parse(input).into_iter().map(calc_fuel_repeating).sum()
+    parse(input).map(calc_fuel_repeating).sum()
 }
 
 fn calc_fuel(mass: i32) -> i32 {
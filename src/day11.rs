@@ -0,0 +1,196 @@
+use std::{collections::HashMap, fs};
+
+use crate::{
+    day3::Position,
+    day8::{BLACK, WHITE},
+    intcode::{parse, IntcodeComputer, State},
+};
+
+const PATH: &str = "src/day11.txt";
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 11: \n a) {} \n b) \n{}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> usize {
+    paint(&parse(input), Color::Black).len()
+}
+
+fn solve_part_two(input: &str) -> String {
+    render(&paint(&parse(input), Color::White))
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Color {
+    Black,
+    White,
+}
+
+impl Color {
+    fn from(value: i64) -> Color {
+        match value {
+            0 => Color::Black,
+            1 => Color::White,
+            _ => panic!("Color {} is not recognized!", value),
+        }
+    }
+
+    fn value(&self) -> i64 {
+        match self {
+            Color::Black => 0,
+            Color::White => 1,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+enum Direction {
+    #[default]
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    fn turn(&self, value: i64) -> Direction {
+        match (value, self) {
+            (0, Direction::Up) | (1, Direction::Down) => Direction::Left,
+            (0, Direction::Right) | (1, Direction::Left) => Direction::Up,
+            (0, Direction::Down) | (1, Direction::Up) => Direction::Right,
+            (0, Direction::Left) | (1, Direction::Right) => Direction::Down,
+            _ => panic!("Turn {} is not recognized!", value),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Robot {
+    position: Position,
+    direction: Direction,
+}
+
+impl Robot {
+    fn move_forward(&mut self, turn: i64) {
+        self.direction = self.direction.turn(turn);
+
+        match self.direction {
+            Direction::Up => self.position.y -= 1,
+            Direction::Right => self.position.x += 1,
+            Direction::Down => self.position.y += 1,
+            Direction::Left => self.position.x -= 1,
+        }
+    }
+}
+
+/// Runs the robot on a hull whose starting panel has `start` color and returns
+/// every panel it painted at least once.
+fn paint(program: &[i64], start: Color) -> HashMap<Position, Color> {
+    let mut computer = IntcodeComputer::new(program);
+    let mut robot = Robot::default();
+    let mut panels = HashMap::from([(robot.position, start)]);
+    let mut painted = HashMap::new();
+
+    while computer.state() != State::Halted {
+        let color = *panels.get(&robot.position).unwrap_or(&Color::Black);
+        computer.push_input(color.value());
+        computer.run();
+
+        for instruction in computer.take_outputs().chunks(2) {
+            match instruction {
+                [color, turn] => {
+                    panels.insert(robot.position, Color::from(*color));
+                    painted.insert(robot.position, Color::from(*color));
+                    robot.move_forward(*turn);
+                }
+                _ => panic!("Robot got an incomplete instruction {:?}!", instruction),
+            }
+        }
+    }
+
+    painted
+}
+
+fn render(panels: &HashMap<Position, Color>) -> String {
+    let white: Vec<&Position> = panels
+        .iter()
+        .filter(|(_, color)| **color == Color::White)
+        .map(|(position, _)| position)
+        .collect();
+
+    let min_x = white.iter().map(|p| p.x).min().unwrap_or(0);
+    let max_x = white.iter().map(|p| p.x).max().unwrap_or(0);
+    let min_y = white.iter().map(|p| p.y).min().unwrap_or(0);
+    let max_y = white.iter().map(|p| p.y).max().unwrap_or(0);
+
+    let rows: Vec<String> = (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| match panels.get(&Position { x, y }) {
+                    Some(Color::White) => WHITE,
+                    _ => BLACK,
+                })
+                .collect()
+        })
+        .collect();
+
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads a panel color before sending each (color, turn) instruction.
+    fn program_sending(instructions: &[(i64, i64)]) -> Vec<i64> {
+        let mut program: Vec<i64> = instructions
+            .iter()
+            .flat_map(|(color, turn)| [3, 1000, 104, *color, 104, *turn])
+            .collect();
+        program.push(99);
+        program
+    }
+
+    #[test]
+    fn should_turn() {
+        assert_eq!(Direction::Up.turn(0), Direction::Left);
+        assert_eq!(Direction::Up.turn(1), Direction::Right);
+        assert_eq!(Direction::Left.turn(0), Direction::Down);
+        assert_eq!(Direction::Left.turn(1), Direction::Up);
+    }
+
+    #[test]
+    fn should_count_painted_panels() {
+        let program = program_sending(&[(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)]);
+
+        assert_eq!(paint(&program, Color::Black).len(), 6);
+    }
+
+    #[test]
+    fn should_render_white_panels() {
+        let program = program_sending(&[(1, 1), (1, 1), (1, 1), (0, 1)]);
+
+        assert_eq!(render(&paint(&program, Color::Black)), "⬜⬜\n⬛⬜");
+    }
+
+    // the answers for this input are not recorded yet, so only check that the robot
+    // painted something and the six rows high registration identifier
+    #[test]
+    fn should_solve_part_one() {
+        let input: String = fs::read_to_string(PATH).unwrap();
+
+        assert_ne!(solve_part_one(&input), 0);
+    }
+
+    #[test]
+    fn should_solve_part_two() {
+        let input: String = fs::read_to_string(PATH).unwrap();
+
+        assert_eq!(solve_part_two(&input).lines().count(), 6);
+    }
+}
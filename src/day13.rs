@@ -0,0 +1,263 @@
+use std::{collections::HashMap, env, fs, io::Write};
+
+use crate::{
+    day3::Position,
+    intcode::{parse, IntcodeComputer, State},
+};
+
+const PATH: &str = "src/day13.txt";
+
+/// Set this environment variable to watch the autopilot play part two.
+const WATCH: &str = "WATCH_ARCADE";
+
+/// Set this environment variable to a file to record the watched game into.
+const RECORD: &str = "RECORD_ARCADE";
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 13: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> usize {
+    let mut arcade = Arcade::new(&parse(input));
+    arcade.run();
+
+    arcade
+        .screen
+        .values()
+        .filter(|t| **t == Tile::Block)
+        .count()
+}
+
+fn solve_part_two(input: &str) -> i64 {
+    let mut program = parse(input);
+    program[0] = 2;
+
+    let mut arcade = Arcade::new(&program);
+    if env::var(WATCH).is_err() {
+        return arcade.play(None);
+    }
+
+    let record = env::var(RECORD).ok();
+    if record.is_some() {
+        arcade.computer.start_recording();
+    }
+
+    let score = arcade.play(Some(&mut std::io::stdout()));
+
+    if let (Some(path), Some(session)) = (record, arcade.computer.take_session()) {
+        session.save(path).unwrap();
+    }
+    score
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    fn from(value: i64) -> Tile {
+        match value {
+            0 => Tile::Empty,
+            1 => Tile::Wall,
+            2 => Tile::Block,
+            3 => Tile::Paddle,
+            4 => Tile::Ball,
+            _ => panic!("Tile {} is not recognized!", value),
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '█',
+            Tile::Block => '▒',
+            Tile::Paddle => '▬',
+            Tile::Ball => '●',
+        }
+    }
+}
+
+struct Arcade {
+    computer: IntcodeComputer,
+    screen: HashMap<Position, Tile>,
+    score: i64,
+}
+
+impl Arcade {
+    fn new(program: &[i64]) -> Arcade {
+        Arcade {
+            computer: IntcodeComputer::new(program),
+            screen: HashMap::new(),
+            score: 0,
+        }
+    }
+
+    /// Runs until the game waits for the joystick or ends and draws its output.
+    fn run(&mut self) -> State {
+        let state = self.computer.run();
+
+        for output in self.computer.take_outputs().chunks(3) {
+            match output {
+                [-1, 0, score] => self.score = *score,
+                [x, y, tile] => {
+                    let position = Position {
+                        x: *x as i32,
+                        y: *y as i32,
+                    };
+                    self.screen.insert(position, Tile::from(*tile));
+                }
+                _ => panic!("Arcade got an incomplete output {:?}!", output),
+            }
+        }
+
+        state
+    }
+
+    fn find(&self, tile: Tile) -> Option<Position> {
+        self.screen
+            .iter()
+            .find(|(_, t)| **t == tile)
+            .map(|(position, _)| *position)
+    }
+
+    /// Tilts the joystick towards the ball.
+    fn joystick(&self) -> i64 {
+        match (self.find(Tile::Ball), self.find(Tile::Paddle)) {
+            (Some(ball), Some(paddle)) => i64::from((ball.x - paddle.x).signum()),
+            _ => 0,
+        }
+    }
+
+    /// Plays the game with the autopilot until it ends and returns the final
+    /// score. Every frame is drawn to `frames` if one is given.
+    fn play(&mut self, mut frames: Option<&mut dyn Write>) -> i64 {
+        while self.run() != State::Halted {
+            if let Some(frames) = frames.as_mut() {
+                write!(frames, "\x1b[2J\x1b[H{}", self.render()).unwrap();
+                frames.flush().unwrap();
+            }
+
+            self.computer.push_input(self.joystick());
+        }
+
+        self.score
+    }
+
+    fn render(&self) -> String {
+        let max_x = self.screen.keys().map(|p| p.x).max().unwrap_or(0);
+        let max_y = self.screen.keys().map(|p| p.y).max().unwrap_or(0);
+
+        let mut frame = format!("Score: {}\n", self.score);
+
+        for y in 0..=max_y {
+            let row: String = (0..=max_x)
+                .map(|x| {
+                    self.screen
+                        .get(&Position { x, y })
+                        .unwrap_or(&Tile::Empty)
+                        .symbol()
+                })
+                .collect();
+            frame.push_str(&row);
+            frame.push('\n');
+        }
+
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // draws a wall, a block, the ball at (3, 1) and the paddle at (1, 2), reads the
+    // joystick and then sends it plus one as the score
+    const GAME: &str = "104,0,104,0,104,1,104,1,104,0,104,2,104,3,104,1,104,4,104,1,104,2,104,3,3,100,1001,100,1,100,104,-1,104,0,4,100,99";
+
+    #[test]
+    fn should_draw_screen() {
+        let mut arcade = Arcade::new(&parse(GAME));
+
+        assert_eq!(arcade.run(), State::WaitingForInput);
+        assert_eq!(arcade.find(Tile::Ball), Some(Position { x: 3, y: 1 }));
+        assert_eq!(arcade.render(), "Score: 0\n█▒  \n   ●\n ▬  \n");
+    }
+
+    #[test]
+    fn should_steer_paddle_towards_ball() {
+        let mut arcade = Arcade::new(&parse(GAME));
+        arcade.run();
+
+        assert_eq!(arcade.joystick(), 1);
+    }
+
+    #[test]
+    fn should_play_until_the_end() {
+        let mut frames: Vec<u8> = vec![];
+
+        assert_eq!(Arcade::new(&parse(GAME)).play(Some(&mut frames)), 2);
+        assert!(String::from_utf8(frames)
+            .unwrap()
+            .starts_with("\x1b[2J\x1b[HScore: 0\n"));
+    }
+
+    #[test]
+    fn should_record_watched_game() {
+        let mut arcade = Arcade::new(&parse(GAME));
+        arcade.computer.start_recording();
+        arcade.play(None);
+
+        let session = arcade.computer.take_session().unwrap();
+
+        // the joystick points right after the twelve drawing outputs
+        assert!(session.to_string().contains("\n11 out 3\n12 in 1\n"));
+        assert_eq!(session.replay(&parse(GAME)), Ok(()));
+    }
+
+    #[test]
+    fn should_count_blocks() {
+        assert_eq!(
+            solve_part_one(GAME.replace("3,100,", "99,100,").as_str()),
+            1
+        );
+    }
+
+    // the answers for this input are not recorded yet, so check part one against
+    // the first screen of the paid game
+    #[test]
+    fn should_solve_part_one() {
+        let input: String = fs::read_to_string(PATH).unwrap();
+        let mut program = parse(&input);
+        program[0] = 2;
+
+        let mut arcade = Arcade::new(&program);
+        arcade.run();
+        let blocks = arcade.screen.values().filter(|t| **t == Tile::Block);
+
+        assert_eq!(solve_part_one(&input), blocks.count());
+    }
+
+    // and part two against the score of a game that broke every block
+    #[test]
+    fn should_solve_part_two() {
+        let input: String = fs::read_to_string(PATH).unwrap();
+        let mut program = parse(&input);
+        program[0] = 2;
+
+        let mut arcade = Arcade::new(&program);
+        let score = arcade.play(None);
+
+        assert_eq!(arcade.find(Tile::Block), None);
+        assert_eq!(solve_part_two(&input), score);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+};
+
+use crate::{
+    day3::Position,
+    intcode::{parse, IntcodeComputer},
+};
+
+const PATH: &str = "src/day15.txt";
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 15: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> usize {
+    let (_, oxygen_distance) = explore(&parse(input));
+
+    oxygen_distance.expect("Droid did not find the oxygen system!")
+}
+
+fn solve_part_two(input: &str) -> usize {
+    let (cells, _) = explore(&parse(input));
+
+    let oxygen = cells
+        .iter()
+        .find(|(_, cell)| **cell == Cell::Oxygen)
+        .map(|(position, _)| *position)
+        .expect("Droid did not find the oxygen system!");
+
+    fill_time(&cells, oxygen)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Cell {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+impl Cell {
+    fn from(status: i64) -> Cell {
+        match status {
+            0 => Cell::Wall,
+            1 => Cell::Open,
+            2 => Cell::Oxygen,
+            _ => panic!("Status {} is not recognized!", status),
+        }
+    }
+}
+
+/// Movement commands understood by the droid in the order north, south, west, east.
+const MOVEMENTS: [(i64, i32, i32); 4] = [(1, 0, -1), (2, 0, 1), (3, -1, 0), (4, 1, 0)];
+
+fn neighbours(position: Position) -> impl Iterator<Item = (i64, Position)> {
+    MOVEMENTS.iter().map(move |(command, x, y)| {
+        (
+            *command,
+            Position {
+                x: position.x + x,
+                y: position.y + y,
+            },
+        )
+    })
+}
+
+/// Maps the whole maze breadth first. Instead of walking the droid back, every
+/// reached cell keeps a clone of the droid standing on it to branch off from.
+///
+/// Returns the map and the number of moves to the oxygen system, if there is one.
+fn explore(program: &[i64]) -> (HashMap<Position, Cell>, Option<usize>) {
+    let start = Position::default();
+
+    let mut cells = HashMap::from([(start, Cell::Open)]);
+    let mut oxygen_distance = None;
+    let mut queue = VecDeque::from([(start, IntcodeComputer::new(program), 0)]);
+
+    while let Some((position, droid, distance)) = queue.pop_front() {
+        for (command, next) in neighbours(position) {
+            if cells.contains_key(&next) {
+                continue;
+            }
+
+            let mut moved = droid.clone();
+            moved.push_input(command);
+            moved.run();
+
+            let status = *moved
+                .take_outputs()
+                .last()
+                .expect("Droid did not report a status!");
+            let cell = Cell::from(status);
+
+            cells.insert(next, cell);
+
+            match cell {
+                Cell::Wall => continue,
+                Cell::Oxygen => oxygen_distance = oxygen_distance.or(Some(distance + 1)),
+                Cell::Open => {}
+            }
+
+            queue.push_back((next, moved, distance + 1));
+        }
+    }
+
+    (cells, oxygen_distance)
+}
+
+/// Minutes until oxygen spreading from `start` reaches every open cell.
+fn fill_time(cells: &HashMap<Position, Cell>, start: Position) -> usize {
+    let mut minutes = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(position) = queue.pop_front() {
+        let minute = minutes[&position];
+
+        for (_, next) in neighbours(position) {
+            if minutes.contains_key(&next) || cells.get(&next).is_none_or(|c| *c == Cell::Wall) {
+                continue;
+            }
+
+            minutes.insert(next, minute + 1);
+            queue.push_back(next);
+        }
+    }
+
+    *minutes.values().max().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a droid in a corridor of three cells along the x axis, oxygen at the east end
+    const CORRIDOR: &str = "3,100,1008,100,4,101,1005,101,21,1008,100,3,101,1005,101,49,104,0,1105,1,0,1007,102,2,101,1006,101,16,1001,102,1,102,1008,102,2,101,1005,101,44,104,1,1105,1,0,104,2,1105,1,0,107,0,102,101,1006,101,16,101,-1,102,102,104,1,1105,1,0";
+
+    fn parse_map(map: &str) -> HashMap<Position, Cell> {
+        map.lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars().enumerate().map(move |(x, c)| {
+                    let position = Position {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    match c {
+                        '#' => (position, Cell::Wall),
+                        'O' => (position, Cell::Oxygen),
+                        _ => (position, Cell::Open),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_explore_maze() {
+        let (cells, oxygen_distance) = explore(&parse(CORRIDOR));
+
+        assert_eq!(oxygen_distance, Some(2));
+        assert_eq!(cells.values().filter(|c| **c == Cell::Wall).count(), 8);
+        assert_eq!(cells[&Position { x: 2, y: 0 }], Cell::Oxygen);
+    }
+
+    #[test]
+    fn should_fill_with_oxygen() {
+        let cells = parse_map(" ##   \n#..## \n#.#..#\n#.O.# \n ###  ");
+
+        assert_eq!(fill_time(&cells, Position { x: 2, y: 3 }), 4);
+    }
+
+    #[test]
+    fn should_solve_with_corridor_droid() {
+        assert_eq!(solve_part_one(CORRIDOR), 2);
+        assert_eq!(solve_part_two(CORRIDOR), 2);
+    }
+}
@@ -0,0 +1,316 @@
+use std::fs;
+
+use crate::intcode::{parse, IntcodeComputer};
+
+const PATH: &str = "src/day17.txt";
+
+const MAX_ROUTINE_LENGTH: usize = 20;
+const FUNCTION_NAMES: [&str; 3] = ["A", "B", "C"];
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 17: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> usize {
+    let grid = read_camera(&parse(input));
+
+    alignment_parameters(&grid).iter().sum()
+}
+
+fn solve_part_two(input: &str) -> i64 {
+    let mut program = parse(input);
+    let grid = read_camera(&program);
+
+    let moves = find_path(&grid);
+    let routines = compress(&moves, MAX_ROUTINE_LENGTH)
+        .expect("Path could not be compressed into three functions!");
+
+    program[0] = 2;
+    let mut computer = IntcodeComputer::new(&program);
+
+    movement_input(&routines)
+        .iter()
+        .for_each(|l| computer.push_line(l));
+    computer.run();
+
+    *computer
+        .take_ascii_output()
+        .values
+        .last()
+        .expect("Robot did not report the collected dust!")
+}
+
+fn read_camera(program: &[i64]) -> Vec<Vec<char>> {
+    let mut computer = IntcodeComputer::new(program);
+    computer.run();
+
+    parse_grid(&computer.take_ascii_output().text)
+}
+
+fn parse_grid(text: &str) -> Vec<Vec<char>> {
+    text.lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.chars().collect())
+        .collect()
+}
+
+fn is_scaffold(grid: &[Vec<char>], x: i32, y: i32) -> bool {
+    if x < 0 || y < 0 {
+        return false;
+    }
+
+    grid.get(y as usize)
+        .and_then(|row| row.get(x as usize))
+        .is_some_and(|c| *c != '.' && *c != 'X')
+}
+
+fn alignment_parameters(grid: &[Vec<char>]) -> Vec<usize> {
+    let mut parameters = vec![];
+
+    for (y, row) in grid.iter().enumerate() {
+        for x in 0..row.len() {
+            let (x, y) = (x as i32, y as i32);
+
+            let intersection = [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)]
+                .iter()
+                .all(|(dx, dy)| is_scaffold(grid, x + dx, y + dy));
+
+            if intersection {
+                parameters.push((x * y) as usize);
+            }
+        }
+    }
+
+    parameters
+}
+
+/// Follows the scaffold from the robot to its end, going straight over
+/// intersections, and returns every move as turn and step count like `R,8`.
+fn find_path(grid: &[Vec<char>]) -> Vec<String> {
+    let (mut x, mut y, mut direction) = grid
+        .iter()
+        .enumerate()
+        .find_map(|(y, row)| {
+            row.iter().enumerate().find_map(|(x, c)| {
+                let direction = match c {
+                    '^' => (0, -1),
+                    'v' => (0, 1),
+                    '<' => (-1, 0),
+                    '>' => (1, 0),
+                    _ => return None,
+                };
+                Some((x as i32, y as i32, direction))
+            })
+        })
+        .expect("Camera does not show the robot!");
+
+    let mut moves = vec![];
+
+    loop {
+        let left = (direction.1, -direction.0);
+        let right = (-direction.1, direction.0);
+
+        let turn = if is_scaffold(grid, x + left.0, y + left.1) {
+            direction = left;
+            'L'
+        } else if is_scaffold(grid, x + right.0, y + right.1) {
+            direction = right;
+            'R'
+        } else {
+            break;
+        };
+
+        let mut steps = 0;
+        while is_scaffold(grid, x + direction.0, y + direction.1) {
+            x += direction.0;
+            y += direction.1;
+            steps += 1;
+        }
+
+        moves.push(format!("{},{}", turn, steps));
+    }
+
+    moves
+}
+
+#[derive(Debug, PartialEq)]
+struct Routines {
+    main: String,
+    functions: Vec<String>,
+}
+
+/// Splits `moves` into a main routine calling up to three movement functions,
+/// where neither the main routine nor any function is longer than `max_length`.
+fn compress(moves: &[String], max_length: usize) -> Option<Routines> {
+    let mut functions: Vec<&[String]> = vec![];
+    let mut calls: Vec<usize> = vec![];
+
+    if !compress_from(moves, max_length, &mut functions, &mut calls) {
+        return None;
+    }
+
+    Some(Routines {
+        main: calls
+            .iter()
+            .map(|c| FUNCTION_NAMES[*c])
+            .collect::<Vec<_>>()
+            .join(","),
+        functions: functions.iter().map(|f| f.join(",")).collect(),
+    })
+}
+
+/// Lines the robot reads before moving: the main routine, all three functions,
+/// even unused ones left empty, and declining the video feed.
+fn movement_input(routines: &Routines) -> Vec<String> {
+    let mut lines = vec![routines.main.clone()];
+    lines.extend(routines.functions.iter().cloned());
+    lines.resize(1 + FUNCTION_NAMES.len(), String::new());
+    lines.push("n".to_string());
+    lines
+}
+
+fn compress_from<'a>(
+    moves: &'a [String],
+    max_length: usize,
+    functions: &mut Vec<&'a [String]>,
+    calls: &mut Vec<usize>,
+) -> bool {
+    if moves.is_empty() {
+        return true;
+    }
+
+    // every call takes one letter and one comma
+    if 2 * (calls.len() + 1) - 1 > max_length {
+        return false;
+    }
+
+    for (i, function) in functions.clone().into_iter().enumerate() {
+        if moves.starts_with(function) {
+            calls.push(i);
+            if compress_from(&moves[function.len()..], max_length, functions, calls) {
+                return true;
+            }
+            calls.pop();
+        }
+    }
+
+    if functions.len() == FUNCTION_NAMES.len() {
+        return false;
+    }
+
+    for length in 1..=moves.len() {
+        let function = &moves[..length];
+
+        if function.join(",").len() > max_length {
+            break;
+        }
+
+        functions.push(function);
+        calls.push(functions.len() - 1);
+        if compress_from(&moves[length..], max_length, functions, calls) {
+            return true;
+        }
+        calls.pop();
+        functions.pop();
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERSECTIONS: &str = "..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..";
+
+    const SCAFFOLD: &str = "#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......";
+
+    const EXAMPLE_PATH: &str = "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2";
+
+    fn to_moves(path: &str) -> Vec<String> {
+        let parts: Vec<&str> = path.split(',').collect();
+        parts.chunks(2).map(|m| m.join(",")).collect()
+    }
+
+    #[test]
+    fn should_sum_alignment_parameters() {
+        let grid = parse_grid(INTERSECTIONS);
+
+        assert_eq!(alignment_parameters(&grid), vec![4, 8, 24, 40]);
+    }
+
+    #[test]
+    fn should_read_camera_from_ascii_output() {
+        let mut program: Vec<i64> = INTERSECTIONS
+            .bytes()
+            .flat_map(|b| [104, i64::from(b)])
+            .collect();
+        program.push(99);
+
+        assert_eq!(read_camera(&program), parse_grid(INTERSECTIONS));
+    }
+
+    #[test]
+    fn should_find_path() {
+        assert_eq!(find_path(&parse_grid(SCAFFOLD)), to_moves(EXAMPLE_PATH));
+    }
+
+    #[test]
+    fn should_compress_path() {
+        let moves = to_moves(EXAMPLE_PATH);
+        let routines = compress(&moves, MAX_ROUTINE_LENGTH).unwrap();
+
+        let expanded: Vec<&str> = routines
+            .main
+            .split(',')
+            .map(|name| {
+                let index = FUNCTION_NAMES.iter().position(|n| *n == name).unwrap();
+                routines.functions[index].as_str()
+            })
+            .collect();
+
+        assert_eq!(expanded.join(","), EXAMPLE_PATH);
+        assert!(routines.main.len() <= MAX_ROUTINE_LENGTH);
+        assert!(routines
+            .functions
+            .iter()
+            .all(|f| f.len() <= MAX_ROUTINE_LENGTH));
+    }
+
+    #[test]
+    fn should_send_unused_functions_as_empty_lines() {
+        let routines = compress(&to_moves("R,8,R,8,R,8"), MAX_ROUTINE_LENGTH).unwrap();
+
+        assert_eq!(movement_input(&routines), vec!["A,A,A", "R,8", "", "", "n"]);
+    }
+
+    #[test]
+    fn should_not_compress_too_long_path() {
+        assert_eq!(compress(&to_moves(EXAMPLE_PATH), 8), None);
+    }
+}
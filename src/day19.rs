@@ -0,0 +1,107 @@
+use std::fs;
+
+use crate::intcode::{parse, IntcodeComputer};
+
+const PATH: &str = "src/day19.txt";
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 19: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> usize {
+    let drone = Drone::new(input);
+
+    (0..50)
+        .flat_map(|y| (0..50).map(move |x| (x, y)))
+        .filter(|(x, y)| drone.is_pulled(*x, *y))
+        .count()
+}
+
+fn solve_part_two(input: &str) -> i64 {
+    let drone = Drone::new(input);
+
+    let (x, y) = find_square(&drone, 100);
+
+    x * 10000 + y
+}
+
+/// Holds the parsed drone program, every query boots a fresh copy of it.
+struct Drone {
+    program: Vec<i64>,
+}
+
+impl Drone {
+    fn new(input: &str) -> Drone {
+        Drone {
+            program: parse(input),
+        }
+    }
+
+    fn is_pulled(&self, x: i64, y: i64) -> bool {
+        let mut computer = IntcodeComputer::new(&self.program);
+        computer.push_input(x);
+        computer.push_input(y);
+        computer.run();
+
+        match computer.take_outputs().as_slice() {
+            [0] => false,
+            [1] => true,
+            outputs => panic!("Drone reported {:?} for {},{}!", outputs, x, y),
+        }
+    }
+}
+
+/// Finds the top left corner of the closest `size` x `size` square inside the beam.
+///
+/// Walks down the left edge of the beam row by row and checks whether the top
+/// right corner of a square standing on that edge is still pulled.
+fn find_square(drone: &Drone, size: i64) -> (i64, i64) {
+    let mut x = 0;
+    let mut y = size - 1;
+
+    loop {
+        // rows close to the emitter can miss the beam entirely
+        if let Some(edge) = (x..=x + y).find(|x| drone.is_pulled(*x, y)) {
+            x = edge;
+
+            if drone.is_pulled(x + size - 1, y - size + 1) {
+                return (x, y - size + 1);
+            }
+        }
+
+        y += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // pulls every point with x <= y <= 2x
+    const CONE: &str = "3,100,3,101,7,101,100,102,1002,100,2,103,7,103,101,104,1,102,104,105,1008,105,0,106,4,106,99";
+
+    #[test]
+    fn should_query_drone() {
+        let drone = Drone::new(CONE);
+
+        assert!(drone.is_pulled(0, 0));
+        assert!(drone.is_pulled(3, 5));
+        assert!(!drone.is_pulled(3, 7));
+        assert!(!drone.is_pulled(5, 3));
+    }
+
+    #[test]
+    fn should_find_square() {
+        assert_eq!(find_square(&Drone::new(CONE), 10), (18, 27));
+    }
+
+    #[test]
+    fn should_count_pulled_points() {
+        assert_eq!(solve_part_one(CONE), 650);
+    }
+}
@@ -1,7 +1,12 @@
-use std::fs;
+use std::{env, fs};
+
+use crate::intcode::{self, dump};
 
 const PATH: &str = "src/day2.txt";
 
+/// Set this environment variable to see everything part one writes to memory.
+const DIFF: &str = "DIFF_GRAVITY_ASSIST";
+
 pub fn solve() {
     let input: String = fs::read_to_string(PATH).unwrap();
 
@@ -10,12 +15,18 @@ pub fn solve() {
         solve_part_one(&input),
         solve_part_two(&input)
     );
+
+    if env::var(DIFF).is_ok() {
+        let mut intcode_program = parse(&input);
+        initialize(&mut intcode_program, 12, 2);
+        print!("{}", run_with_diff(&mut intcode_program));
+    }
 }
 
 fn parse(input: &str) -> Vec<i32> {
-    input
-        .split(',')
-        .map(|s| s.parse::<i32>().unwrap())
+    intcode::parse(input)
+        .into_iter()
+        .map(|v| i32::try_from(v).unwrap_or_else(|_| panic!("Value {} does not fit into i32!", v)))
         .collect()
 }
 
@@ -24,7 +35,7 @@ fn initialize(input: &mut [i32], first: i32, second: i32) {
     input[2] = second;
 }
 
-fn run_operations(input: &mut [i32]) {
+pub fn run_operations(input: &mut [i32]) {
     let mut i = 0;
     loop {
         let value = match input[i] {
@@ -39,6 +50,15 @@ fn run_operations(input: &mut [i32]) {
     }
 }
 
+/// Runs the program and reports which cells it changed.
+fn run_with_diff(input: &mut [i32]) -> String {
+    let before: Vec<i64> = input.iter().map(|v| i64::from(*v)).collect();
+    run_operations(input);
+    let after: Vec<i64> = input.iter().map(|v| i64::from(*v)).collect();
+
+    dump::report(&before, &after)
+}
+
 fn solve_part_one(input: &str) -> i32 {
     let mut intcode_program = parse(input);
 
@@ -49,7 +69,6 @@ fn solve_part_one(input: &str) -> i32 {
 }
 
 fn solve_part_two(input: &str) -> i32 {
-
     for noun in 0..100 {
         for verb in 0..100 {
             let mut intcode_program = parse(input);
@@ -98,6 +117,17 @@ mod tests {
         assert_eq!(input, vec![1, 12, 2, 1, 99]);
     }
 
+    #[test]
+    fn should_report_writes() {
+        let mut input = parse("1,9,10,3,2,3,11,0,99,30,40,50");
+
+        assert_eq!(
+            run_with_diff(&mut input),
+            "0..1\n      0: 1 -> 3500\n  code 0: add [9], [10], [3] => data 3500\n\
+             3..4\n      3: 3 -> 70\n  code 0: add [9], [10], [3] => data 3500\n"
+        );
+    }
+
     #[test]
     fn should_solve_part_one() {
         let input: String = fs::read_to_string(PATH).unwrap();
@@ -0,0 +1,348 @@
+use std::fs;
+
+use crate::intcode::{parse, IntcodeComputer};
+
+const PATH: &str = "src/day21.txt";
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+// jump if there is a hole in the next three tiles and ground to land on
+const WALK_SCRIPT: &str = "NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J
+WALK";
+
+// as above, but only if the droid can step or jump again after landing
+const RUN_SCRIPT: &str = "NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J
+NOT E T
+NOT T T
+OR H T
+AND T J
+RUN";
+
+// hulls the springdroid showed while falling, scripts are checked against them first
+const WALK_HULLS: [&str; 3] = [
+    "#####.###########",
+    "#####..#.########",
+    "#####...#########",
+];
+const RUN_HULLS: [&str; 4] = [
+    "#####.###########",
+    "#####..#.########",
+    "#####...#########",
+    "#####.#.##..#.###",
+];
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+    println!(
+        "Day 21: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> i64 {
+    submit(
+        &parse(input),
+        &Script::parse(WALK_SCRIPT).unwrap(),
+        &WALK_HULLS,
+    )
+}
+
+fn solve_part_two(input: &str) -> i64 {
+    submit(
+        &parse(input),
+        &Script::parse(RUN_SCRIPT).unwrap(),
+        &RUN_HULLS,
+    )
+}
+
+/// Sends the script to the springdroid and returns the reported hull damage.
+/// The script is simulated on the known `hulls` first to fail without running the droid.
+fn submit(program: &[i64], script: &Script, hulls: &[&str]) -> i64 {
+    for hull in hulls {
+        if let Some(tile) = script.simulate(hull) {
+            panic!("Springdroid would fall into tile {} of {}!", tile, hull);
+        }
+    }
+
+    let mut computer = IntcodeComputer::new(program);
+
+    script.lines().iter().for_each(|l| computer.push_line(l));
+    computer.run();
+
+    let output = computer.take_ascii_output();
+
+    *output
+        .values
+        .last()
+        .unwrap_or_else(|| panic!("Springdroid fell into space:\n{}", output.text))
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Operation {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Instruction {
+    operation: Operation,
+    read: char,
+    write: char,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    /// Registers holding the ground sensors, one tile further away each.
+    fn sensors(&self) -> &'static str {
+        match self {
+            Mode::Walk => "ABCD",
+            Mode::Run => "ABCDEFGHI",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Script {
+    instructions: Vec<Instruction>,
+    mode: Mode,
+}
+
+impl Script {
+    /// Parses a springscript program ending in `WALK` or `RUN` and checks it the
+    /// way the springdroid would.
+    fn parse(text: &str) -> Result<Script, String> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        let (last, instructions) = lines
+            .split_last()
+            .ok_or_else(|| String::from("Script is empty"))?;
+
+        let mode = match *last {
+            "WALK" => Mode::Walk,
+            "RUN" => Mode::Run,
+            _ => return Err(format!("Script must end with WALK or RUN, not {}", last)),
+        };
+
+        if instructions.len() > MAX_INSTRUCTIONS {
+            return Err(format!(
+                "Script has {} instructions, at most {} fit into memory",
+                instructions.len(),
+                MAX_INSTRUCTIONS
+            ));
+        }
+
+        let instructions = instructions
+            .iter()
+            .enumerate()
+            .map(|(number, line)| {
+                parse_instruction(line, mode).map_err(|e| format!("Line {}: {}", number + 1, e))
+            })
+            .collect::<Result<Vec<Instruction>, String>>()?;
+
+        Ok(Script { instructions, mode })
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .instructions
+            .iter()
+            .map(|i| {
+                let operation = match i.operation {
+                    Operation::And => "AND",
+                    Operation::Or => "OR",
+                    Operation::Not => "NOT",
+                };
+                format!("{} {} {}", operation, i.read, i.write)
+            })
+            .collect();
+
+        lines.push(String::from(match self.mode {
+            Mode::Walk => "WALK",
+            Mode::Run => "RUN",
+        }));
+
+        lines
+    }
+
+    /// Decides whether to jump, `ground` tells for each sensor whether it sees ground.
+    fn jumps(&self, ground: &[bool]) -> bool {
+        let mut temporary = false;
+        let mut jump = false;
+
+        for instruction in &self.instructions {
+            let read = match instruction.read {
+                'T' => temporary,
+                'J' => jump,
+                sensor => ground[(sensor as u8 - b'A') as usize],
+            };
+            let write = match instruction.write {
+                'T' => &mut temporary,
+                _ => &mut jump,
+            };
+
+            *write = match instruction.operation {
+                Operation::And => read && *write,
+                Operation::Or => read || *write,
+                Operation::Not => !read,
+            };
+        }
+
+        jump
+    }
+
+    /// Lets the droid cross `hull`, given as `#` for ground and `.` for holes with
+    /// the droid standing on the first tile. Returns the tile it fell into, if any.
+    fn simulate(&self, hull: &str) -> Option<usize> {
+        let tiles: Vec<bool> = hull.chars().map(|c| c == '#').collect();
+        let sensors = self.mode.sensors().len();
+
+        let mut position = 0;
+
+        while position < tiles.len() {
+            let ground: Vec<bool> = (1..=sensors)
+                .map(|distance| *tiles.get(position + distance).unwrap_or(&true))
+                .collect();
+
+            position += if self.jumps(&ground) { 4 } else { 1 };
+
+            if tiles.get(position) == Some(&false) {
+                return Some(position);
+            }
+        }
+
+        None
+    }
+}
+
+fn parse_instruction(line: &str, mode: Mode) -> Result<Instruction, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let [operation, read, write] = words.as_slice() else {
+        return Err(format!("{} is not an instruction with two registers", line));
+    };
+
+    let operation = match *operation {
+        "AND" => Operation::And,
+        "OR" => Operation::Or,
+        "NOT" => Operation::Not,
+        _ => return Err(format!("{} is not an operation", operation)),
+    };
+
+    let read = match *read {
+        "T" | "J" => read.chars().next().unwrap(),
+        r if r.len() == 1 && mode.sensors().contains(r) => r.chars().next().unwrap(),
+        r => {
+            return Err(format!(
+                "{} is not a readable register in {:?} mode",
+                r, mode
+            ))
+        }
+    };
+
+    let write = match *write {
+        "T" | "J" => write.chars().next().unwrap(),
+        w => return Err(format!("{} is not a writable register", w)),
+    };
+
+    Ok(Instruction {
+        operation,
+        read,
+        write,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_script() {
+        let script = Script::parse("NOT A J\nAND D J\nWALK\n").unwrap();
+
+        assert_eq!(script.mode, Mode::Walk);
+        assert_eq!(
+            script.instructions[1],
+            Instruction {
+                operation: Operation::And,
+                read: 'D',
+                write: 'J',
+            }
+        );
+        assert_eq!(script.lines(), vec!["NOT A J", "AND D J", "WALK"]);
+    }
+
+    #[test]
+    fn should_reject_invalid_scripts() {
+        assert_eq!(
+            Script::parse("NOT E J\nWALK"),
+            Err(String::from(
+                "Line 1: E is not a readable register in Walk mode"
+            ))
+        );
+        assert_eq!(
+            Script::parse("NOT A B\nRUN"),
+            Err(String::from("Line 1: B is not a writable register"))
+        );
+        assert_eq!(
+            Script::parse("NOT A J"),
+            Err(String::from(
+                "Script must end with WALK or RUN, not NOT A J"
+            ))
+        );
+        assert!(Script::parse(&format!("{}WALK", "NOT A J\n".repeat(16)))
+            .unwrap_err()
+            .starts_with("Script has 16 instructions"));
+        assert!(Script::parse(RUN_SCRIPT).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Springdroid would fall into tile 5")]
+    fn should_check_script_before_submitting() {
+        submit(&[99], &Script::parse("WALK").unwrap(), &WALK_HULLS);
+    }
+
+    #[test]
+    fn should_simulate_walking() {
+        let script = Script::parse(WALK_SCRIPT).unwrap();
+
+        assert!(WALK_HULLS.iter().all(|h| script.simulate(h).is_none()));
+        assert_eq!(
+            Script::parse("WALK").unwrap().simulate("#####.####"),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn should_simulate_running() {
+        let script = Script::parse(RUN_SCRIPT).unwrap();
+
+        assert!(RUN_HULLS.iter().all(|h| script.simulate(h).is_none()));
+        assert_eq!(
+            Script::parse(&WALK_SCRIPT.replace("WALK", "RUN"))
+                .unwrap()
+                .simulate("#####.#.##..#.###"),
+            Some(7)
+        );
+    }
+}
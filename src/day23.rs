@@ -0,0 +1,187 @@
+use std::fs;
+
+use crate::intcode::{parse, IntcodeComputer};
+
+const PATH: &str = "src/day23.txt";
+
+const COMPUTERS: usize = 50;
+const NAT_ADDRESS: i64 = 255;
+const NO_PACKET: i64 = -1;
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+
+    println!(
+        "Day 23: \n a) {} \n b) {}",
+        solve_part_one(&input),
+        solve_part_two(&input)
+    );
+}
+
+fn solve_part_one(input: &str) -> i64 {
+    let mut network = Network::boot(&parse(input), COMPUTERS);
+
+    loop {
+        let (packets, _) = network.tick();
+
+        for packet in packets {
+            if packet.destination == NAT_ADDRESS {
+                return packet.y;
+            }
+            network.deliver(&packet);
+        }
+    }
+}
+
+fn solve_part_two(input: &str) -> i64 {
+    let mut network = Network::boot(&parse(input), COMPUTERS);
+
+    let mut nat: Option<Packet> = None;
+    let mut last_delivered_y: Option<i64> = None;
+
+    loop {
+        let (packets, idle) = network.tick();
+
+        for packet in packets {
+            if packet.destination == NAT_ADDRESS {
+                nat = Some(packet);
+            } else {
+                network.deliver(&packet);
+            }
+        }
+
+        if !idle {
+            continue;
+        }
+
+        let packet = nat.expect("Network is idle before the NAT received a packet!");
+
+        if last_delivered_y == Some(packet.y) {
+            return packet.y;
+        }
+
+        network.deliver(&Packet {
+            destination: 0,
+            ..packet
+        });
+        last_delivered_y = Some(packet.y);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Packet {
+    destination: i64,
+    x: i64,
+    y: i64,
+}
+
+struct Network {
+    computers: Vec<IntcodeComputer>,
+}
+
+impl Network {
+    fn boot(program: &[i64], size: usize) -> Network {
+        let computers = (0..size)
+            .map(|address| {
+                let mut computer = IntcodeComputer::new(program);
+                computer.push_input(address as i64);
+                computer
+            })
+            .collect();
+
+        Network { computers }
+    }
+
+    /// Runs every computer until it waits for input again, feeding -1 to the ones
+    /// without queued packets. Returns the sent packets and whether the network was
+    /// idle, meaning no computer had anything to receive or send.
+    fn tick(&mut self) -> (Vec<Packet>, bool) {
+        let mut idle = true;
+        let mut packets = vec![];
+
+        for computer in self.computers.iter_mut() {
+            if computer.has_pending_input() {
+                idle = false;
+            } else {
+                computer.push_input(NO_PACKET);
+            }
+
+            computer.run();
+
+            for packet in computer.take_outputs().chunks(3) {
+                match packet {
+                    [destination, x, y] => packets.push(Packet {
+                        destination: *destination,
+                        x: *x,
+                        y: *y,
+                    }),
+                    _ => panic!("Computer sent an incomplete packet {:?}!", packet),
+                }
+            }
+        }
+
+        idle = idle && packets.is_empty();
+
+        (packets, idle)
+    }
+
+    fn deliver(&mut self, packet: &Packet) {
+        let computer = self
+            .computers
+            .get_mut(packet.destination as usize)
+            .unwrap_or_else(|| panic!("No computer has address {}!", packet.destination));
+
+        computer.push_input(packet.x);
+        computer.push_input(packet.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // sends (address, address + 100) to the NAT, then keeps reading input forever
+    const NIC: &str = "3,100,104,255,4,100,1001,100,100,101,4,101,3,102,1105,1,12";
+
+    #[test]
+    fn should_route_packets() {
+        // forwards every packet it receives to address 1
+        let program = "3,100,3,101,3,102,1008,101,-1,103,1005,103,2,104,1,4,101,4,102,1105,1,2";
+        let mut network = Network::boot(&parse(program), 2);
+
+        network.deliver(&Packet {
+            destination: 0,
+            x: 3,
+            y: 4,
+        });
+        let (packets, idle) = network.tick();
+
+        assert_eq!(
+            packets,
+            vec![Packet {
+                destination: 1,
+                x: 3,
+                y: 4
+            }]
+        );
+        assert!(!idle);
+    }
+
+    #[test]
+    fn should_detect_idle_network() {
+        let mut network = Network::boot(&parse(NIC), 3);
+
+        assert_eq!(network.tick().0.len(), 3);
+        assert_eq!(network.tick(), (vec![], true));
+    }
+
+    #[test]
+    fn should_solve_part_one_with_test_nic() {
+        assert_eq!(solve_part_one(NIC), 100);
+    }
+
+    #[test]
+    fn should_solve_part_two_with_test_nic() {
+        assert_eq!(solve_part_two(NIC), 149);
+    }
+}
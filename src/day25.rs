@@ -0,0 +1,465 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{stdin, stdout},
+};
+
+use crate::intcode::{parse, session::Session, IntcodeComputer, State};
+
+const PATH: &str = "src/day25.txt";
+
+/// Set this environment variable to play the adventure in the terminal instead.
+const PLAY: &str = "PLAY_CRYOSTASIS";
+
+/// Set this environment variable to a file to record the played session into.
+const RECORD: &str = "RECORD_CRYOSTASIS";
+
+/// Set this environment variable to a recorded session to check that the
+/// program still plays it the same way.
+const REPLAY: &str = "REPLAY_CRYOSTASIS";
+
+/// Steps after which a command is treated as hanging the droid.
+const MAX_STEPS: usize = 1_000_000;
+
+const CHECKPOINT: &str = "Security Checkpoint";
+
+/// Items that do not kill the droid right away but still end the game.
+const KNOWN_TRAPS: [&str; 1] = ["giant electromagnet"];
+
+pub fn solve() {
+    let input: String = fs::read_to_string(PATH).unwrap();
+
+    if env::var(PLAY).is_ok() {
+        let mut computer = IntcodeComputer::new(&parse(&input));
+        let record = env::var(RECORD).ok();
+        if record.is_some() {
+            computer.start_recording();
+        }
+
+        computer.run_interactive(stdin().lock(), stdout());
+
+        if let (Some(path), Some(session)) = (record, computer.take_session()) {
+            session.save(path).unwrap();
+        }
+        return;
+    }
+
+    if let Ok(path) = env::var(REPLAY) {
+        replay(&input, &path);
+        return;
+    }
+
+    println!("Day 25: \n a) {}", solve_part_one(&input));
+}
+
+/// Prints where the program first plays the recorded session differently,
+/// including the first event it never gets to because it hangs.
+fn replay(input: &str, path: &str) {
+    match Session::load(path).unwrap().replay(&parse(input)) {
+        Ok(()) => println!("Day 25: session replayed without divergence"),
+        Err(divergence) => println!("Day 25: {}", divergence),
+    }
+}
+
+fn solve_part_one(input: &str) -> i64 {
+    let mut droid = Droid {
+        computer: IntcodeComputer::new(&parse(input)),
+    };
+    let intro = droid.read().expect("Droid hung before the first room!");
+
+    let text = Solver::new(droid).solve(&intro);
+
+    find_password(&text).unwrap_or_else(|| panic!("No password in:\n{}", text))
+}
+
+fn find_password(text: &str) -> Option<i64> {
+    text.split_whitespace()
+        .skip_while(|w| *w != "typing")
+        .nth(1)
+        .and_then(|w| w.parse().ok())
+}
+
+/// Line based connection to the droid that can be cloned to restore a snapshot.
+trait Terminal: Clone {
+    /// Sends a command and returns the text up to the next prompt, or `None`
+    /// if the droid got stuck.
+    fn send(&mut self, command: &str) -> Option<String>;
+
+    fn is_alive(&self) -> bool;
+}
+
+#[derive(Clone)]
+struct Droid {
+    computer: IntcodeComputer,
+}
+
+impl Droid {
+    fn read(&mut self) -> Option<String> {
+        if self.computer.run_for(MAX_STEPS) == State::Running {
+            return None;
+        }
+
+        Some(self.computer.take_ascii_output().text)
+    }
+}
+
+impl Terminal for Droid {
+    fn send(&mut self, command: &str) -> Option<String> {
+        self.computer.push_line(command);
+        self.read()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.computer.state() != State::Halted
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+/// Parses the last room described in `text`, there can be several when the
+/// droid gets ejected from the pressure-sensitive floor.
+fn parse_room(text: &str) -> Option<Room> {
+    let description = &text[text.rfind("== ")?..];
+
+    let name = description
+        .lines()
+        .next()?
+        .trim_matches(|c| c == '=' || c == ' ')
+        .to_string();
+
+    let mut room = Room {
+        name,
+        doors: vec![],
+        items: vec![],
+    };
+    let mut list: Option<&mut Vec<String>> = None;
+
+    for line in description.lines().skip(1) {
+        match line {
+            "Doors here lead:" => list = Some(&mut room.doors),
+            "Items here:" => list = Some(&mut room.items),
+            _ => match (line.strip_prefix("- "), list.as_mut()) {
+                (Some(entry), Some(list)) => list.push(entry.to_string()),
+                _ => list = None,
+            },
+        }
+    }
+
+    Some(room)
+}
+
+fn opposite(door: &str) -> &'static str {
+    match door {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        _ => panic!("Door {} is not recognized!", door),
+    }
+}
+
+struct Solver<T: Terminal> {
+    terminal: T,
+    visited: HashSet<String>,
+    inventory: Vec<String>,
+    path_to_checkpoint: Option<Vec<String>>,
+    floor_door: Option<String>,
+    result: Option<String>,
+}
+
+impl<T: Terminal> Solver<T> {
+    fn new(terminal: T) -> Solver<T> {
+        Solver {
+            terminal,
+            visited: HashSet::new(),
+            inventory: vec![],
+            path_to_checkpoint: None,
+            floor_door: None,
+            result: None,
+        }
+    }
+
+    /// Collects every safe item on the ship, then tries item combinations on the
+    /// pressure-sensitive floor. Returns the text shown when the droid got through.
+    fn solve(mut self, intro: &str) -> String {
+        let room = parse_room(intro).expect("Droid does not start in a room!");
+        self.explore(room, &mut vec![]);
+
+        if let Some(text) = self.result {
+            return text;
+        }
+
+        let path = self
+            .path_to_checkpoint
+            .clone()
+            .expect("Droid did not find the security checkpoint!");
+        let floor_door = self
+            .floor_door
+            .clone()
+            .expect("Droid did not find the pressure-sensitive floor!");
+
+        path.iter().for_each(|door| {
+            self.send(door);
+        });
+
+        let items = self.inventory.clone();
+        let mut holding = vec![true; items.len()];
+
+        for combination in 0..1_u32 << items.len() {
+            for (i, item) in items.iter().enumerate() {
+                let wanted = combination & (1 << i) != 0;
+
+                if wanted != holding[i] {
+                    let verb = if wanted { "take" } else { "drop" };
+                    self.send(&format!("{} {}", verb, item));
+                    holding[i] = wanted;
+                }
+            }
+
+            let text = self.send(&floor_door);
+
+            if !self.terminal.is_alive() {
+                return text;
+            }
+        }
+
+        panic!("No item combination got the droid past the floor!");
+    }
+
+    fn send(&mut self, command: &str) -> String {
+        self.terminal
+            .send(command)
+            .unwrap_or_else(|| panic!("Droid got stuck after {}!", command))
+    }
+
+    fn explore(&mut self, room: Room, path: &mut Vec<String>) {
+        self.visited.insert(room.name.clone());
+
+        for item in &room.items {
+            self.take(item);
+        }
+
+        if room.name == CHECKPOINT {
+            self.path_to_checkpoint = Some(path.clone());
+        }
+
+        for door in &room.doors {
+            if path.last().is_some_and(|last| opposite(last) == door) {
+                continue;
+            }
+
+            let text = self.send(door);
+
+            if !self.terminal.is_alive() {
+                // the droid got through with what it already carries
+                self.result = Some(text);
+                return;
+            }
+
+            let next = parse_room(&text).expect("Door does not lead to a room!");
+
+            if next.name == room.name {
+                // the floor threw the droid back
+                self.floor_door = Some(door.clone());
+                continue;
+            }
+
+            if self.visited.contains(&next.name) {
+                self.send(opposite(door));
+                continue;
+            }
+
+            path.push(door.clone());
+            self.explore(next, path);
+            path.pop();
+
+            if self.result.is_some() {
+                return;
+            }
+
+            self.send(opposite(door));
+        }
+    }
+
+    /// Picks up `item` unless it is a known trap, going back to the snapshot
+    /// from before if it turns out to kill or hang the droid.
+    fn take(&mut self, item: &str) {
+        if KNOWN_TRAPS.contains(&item) {
+            return;
+        }
+
+        let snapshot = self.terminal.clone();
+
+        match self.terminal.send(&format!("take {}", item)) {
+            Some(_) if self.terminal.is_alive() => self.inventory.push(item.to_string()),
+            _ => self.terminal = snapshot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small ship: the breach leads north to a kitchen and east to the checkpoint,
+    /// which only lets the droid through when it carries nothing but the mug.
+    #[derive(Clone)]
+    struct Ship {
+        room: &'static str,
+        floor: Vec<(&'static str, &'static str)>,
+        inventory: Vec<&'static str>,
+        alive: bool,
+    }
+
+    impl Ship {
+        fn new() -> Ship {
+            Ship {
+                room: "Hull Breach",
+                floor: vec![
+                    ("Hull Breach", "mug"),
+                    ("Kitchen", "molten lava"),
+                    ("Kitchen", "coin"),
+                    ("Kitchen", "infinite loop"),
+                    (CHECKPOINT, "giant electromagnet"),
+                ],
+                inventory: vec![],
+                alive: true,
+            }
+        }
+
+        fn doors(&self) -> Vec<&'static str> {
+            match self.room {
+                "Hull Breach" => vec!["north", "east"],
+                "Kitchen" => vec!["south"],
+                _ => vec!["west", "east"],
+            }
+        }
+
+        fn items(&self) -> Vec<&'static str> {
+            self.floor
+                .iter()
+                .filter(|(room, _)| *room == self.room)
+                .map(|(_, item)| *item)
+                .collect()
+        }
+
+        fn describe(&self) -> String {
+            let mut text = format!("\n\n\n== {} ==\nA room.\n\nDoors here lead:\n", self.room);
+            self.doors()
+                .iter()
+                .for_each(|d| text.push_str(&format!("- {}\n", d)));
+
+            let items = self.items();
+            if !items.is_empty() {
+                text.push_str("\nItems here:\n");
+                items
+                    .iter()
+                    .for_each(|i| text.push_str(&format!("- {}\n", i)));
+            }
+
+            text + "\nCommand?\n"
+        }
+    }
+
+    impl Terminal for Ship {
+        fn send(&mut self, command: &str) -> Option<String> {
+            if let Some(item) = command.strip_prefix("take ") {
+                let item = self.items().into_iter().find(|i| *i == item).unwrap();
+                match item {
+                    "infinite loop" => return None,
+                    "giant electromagnet" => panic!("Droid took a known trap!"),
+                    "molten lava" => {
+                        self.alive = false;
+                        return Some(String::from("The molten lava is way too hot! You melt!"));
+                    }
+                    _ => {}
+                }
+                self.floor.retain(|(_, i)| *i != item);
+                self.inventory.push(item);
+                return Some(format!("\nYou take the {}.\n\nCommand?\n", item));
+            }
+
+            if let Some(item) = command.strip_prefix("drop ") {
+                let item = self.inventory.iter().copied().find(|i| *i == item).unwrap();
+                self.inventory.retain(|i| *i != item);
+                self.floor.push((self.room, item));
+                return Some(format!("\nYou drop the {}.\n\nCommand?\n", item));
+            }
+
+            self.room = match (self.room, command) {
+                ("Hull Breach", "north") => "Kitchen",
+                ("Hull Breach", "east") => CHECKPOINT,
+                ("Kitchen", "south") | (CHECKPOINT, "west") => "Hull Breach",
+                (CHECKPOINT, "east") => {
+                    if self.inventory == ["mug"] {
+                        self.alive = false;
+                        return Some(String::from(
+                            "You should be able to get in by typing 1234 on the keypad.",
+                        ));
+                    }
+                    return Some(format!(
+                        "\n== Pressure-Sensitive Floor ==\nAlert!\n{}",
+                        self.describe()
+                    ));
+                }
+                _ => panic!("Droid walked into a wall!"),
+            };
+
+            Some(self.describe())
+        }
+
+        fn is_alive(&self) -> bool {
+            self.alive
+        }
+    }
+
+    #[test]
+    fn should_parse_room() {
+        let ship = Ship::new();
+
+        assert_eq!(
+            parse_room(&ship.describe()),
+            Some(Room {
+                name: String::from("Hull Breach"),
+                doors: vec![String::from("north"), String::from("east")],
+                items: vec![String::from("mug")],
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_last_room_after_ejection() {
+        let text = "== Pressure-Sensitive Floor ==\nAlert!\n== Security Checkpoint ==\nDoors here lead:\n- west\n";
+
+        assert_eq!(parse_room(text).unwrap().name, CHECKPOINT);
+    }
+
+    #[test]
+    fn should_recover_from_deadly_items() {
+        let mut ship = Ship::new();
+        ship.send("north");
+        let mut solver = Solver::new(ship);
+
+        solver.take("molten lava");
+        solver.take("infinite loop");
+        solver.take("coin");
+
+        assert!(solver.terminal.is_alive());
+        assert_eq!(solver.inventory, vec!["coin"]);
+    }
+
+    #[test]
+    fn should_solve_adventure() {
+        let ship = Ship::new();
+        let intro = ship.describe();
+
+        let text = Solver::new(ship).solve(&intro);
+
+        assert_eq!(find_password(&text), Some(1234));
+    }
+}
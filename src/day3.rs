@@ -49,9 +49,9 @@ fn solve_part_two(input: &str) -> i32 {
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
-struct Position {
-    x: i32,
-    y: i32,
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Position {
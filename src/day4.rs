@@ -37,10 +37,7 @@ fn to_digits(input: &i32) -> Vec<u32> {
 }
 
 fn count_digit(digits: &[u32], digit: u32) -> u32 {
-    digits
-        .iter()
-        .filter(|d| **d == digit)
-        .count() as u32
+    digits.iter().filter(|d| **d == digit).count() as u32
 }
 
 fn is_increasing(input: &i32) -> bool {
@@ -1,7 +1,18 @@
-use std::fs;
+use std::{
+    env, fs,
+    sync::{Arc, Mutex},
+};
+
+use crate::intcode::{self, coverage::Coverage, IntcodeComputer};
 
 const PATH: &str = "src/day5.txt";
 
+/// Set this environment variable to see which parts of the diagnostic program
+/// both system IDs ran.
+const COVERAGE: &str = "COVER_DIAGNOSTIC";
+
+const SYSTEM_IDS: [i64; 2] = [1, 5];
+
 pub fn solve() {
     let input: String = fs::read_to_string(PATH).unwrap();
 
@@ -10,12 +21,30 @@ pub fn solve() {
         solve_part_one(&input),
         solve_part_two(&input)
     );
+
+    if env::var(COVERAGE).is_ok() {
+        print!("{}", cover_diagnostic(&intcode::parse(&input)).report());
+    }
+}
+
+/// Runs the diagnostic for every system ID on the shared VM.
+fn cover_diagnostic(program: &[i64]) -> Coverage {
+    let coverage = Arc::new(Mutex::new(Coverage::new(program)));
+
+    for system_id in SYSTEM_IDS {
+        let mut computer = IntcodeComputer::new(program);
+        computer.attach(coverage.clone());
+        computer.push_input(system_id);
+        computer.run();
+    }
+
+    Arc::into_inner(coverage).unwrap().into_inner().unwrap()
 }
 
 fn parse(input: &str) -> Vec<i32> {
-    input
-        .split(',')
-        .map(|s| s.parse::<i32>().unwrap())
+    intcode::parse(input)
+        .into_iter()
+        .map(|v| i32::try_from(v).unwrap_or_else(|_| panic!("Value {} does not fit into i32!", v)))
         .collect()
 }
 
@@ -26,10 +55,23 @@ fn extract_param(memory: &[i32], index: usize, immediate_mode: bool) -> i32 {
     }
 }
 
+/// Write parameters are always addresses, any other mode means the operation is broken.
+fn extract_target(memory: &[i32], index: usize, offset: usize, modes: &[char]) -> i32 {
+    let mode = modes[3 - offset];
+    if mode != '0' {
+        panic!(
+            "Operation {} at index {} writes parameter {} in mode {}!",
+            memory[index], index, offset, mode
+        );
+    }
+
+    memory[index + offset]
+}
+
 fn add(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -41,7 +83,7 @@ fn add(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
 fn multiply(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -87,7 +129,7 @@ fn jump_if_false(memory: &[i32], index: usize, modes: &[char]) -> OperationResul
 fn less_than(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -99,7 +141,7 @@ fn less_than(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
 fn equal_to(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -108,8 +150,8 @@ fn equal_to(memory: &[i32], index: usize, modes: &[char]) -> OperationResult {
     }
 }
 
-fn set(memory: &[i32], index: usize, input: i32) -> OperationResult {
-    let first_param = extract_param(memory, index + 1, true);
+fn set(memory: &[i32], index: usize, modes: &[char], input: i32) -> OperationResult {
+    let first_param = extract_target(memory, index, 1, modes);
 
     OperationResult {
         target_index: first_param,
@@ -132,10 +174,21 @@ fn get(memory: &[i32], index: usize, modes: &[char], outputs: &mut Vec<i32>) ->
 
 fn run_operations(memory: &[i32], input: i32) -> Vec<i32> {
     let mut memory = memory.to_vec();
+    run_program(&mut memory, input)
+}
+
+/// Runs the program in place, leaving its final state in `memory`.
+pub fn run_program(memory: &mut [i32], input: i32) -> Vec<i32> {
+    run_program_for(memory, input, usize::MAX).unwrap()
+}
+
+/// Like `run_program`, but gives up after `max_steps` instructions and returns
+/// `None` if the program did not halt by then.
+pub fn run_program_for(memory: &mut [i32], input: i32, max_steps: usize) -> Option<Vec<i32>> {
     let mut outputs: Vec<i32> = vec![];
     let mut index = 0;
 
-    loop {
+    for _ in 0..max_steps {
         let mut current: Vec<char> = memory[index].to_string().chars().collect();
 
         while current.len() < 5 {
@@ -145,15 +198,15 @@ fn run_operations(memory: &[i32], input: i32) -> Vec<i32> {
         let op_code: String = current.iter().rev().take(2).rev().collect();
 
         let op_result = match op_code.as_str() {
-            "99" => break,
-            "01" => add(&memory, index, &current),
-            "02" => multiply(&memory, index, &current),
-            "03" => set(&memory, index, input),
-            "04" => get(&memory, index, &current, &mut outputs),
-            "05" => jump_if_true(&memory, index, &current),
-            "06" => jump_if_false(&memory, index, &current),
-            "07" => less_than(&memory, index, &current),
-            "08" => equal_to(&memory, index, &current),
+            "99" => return Some(outputs),
+            "01" => add(memory, index, &current),
+            "02" => multiply(memory, index, &current),
+            "03" => set(memory, index, &current, input),
+            "04" => get(memory, index, &current, &mut outputs),
+            "05" => jump_if_true(memory, index, &current),
+            "06" => jump_if_false(memory, index, &current),
+            "07" => less_than(memory, index, &current),
+            "08" => equal_to(memory, index, &current),
             _ => panic!("Operation code {} could not be run!", op_code),
         };
 
@@ -163,7 +216,8 @@ fn run_operations(memory: &[i32], input: i32) -> Vec<i32> {
 
         index = op_result.steps;
     }
-    outputs
+
+    None
 }
 
 struct OperationResult {
@@ -191,6 +245,36 @@ mod tests {
         assert_eq!(parse("1,0,0,3"), vec![1, 0, 0, 3]);
     }
 
+    #[test]
+    fn should_write_to_position() {
+        assert_eq!(
+            run_operations(&parse("1101,100,-1,7,4,7,99,0"), 0),
+            vec![99]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation 11101 at index 4 writes parameter 3 in mode 1!")]
+    fn should_reject_immediate_writes() {
+        run_operations(&parse("3,9,4,9,11101,1,1,9,99,0"), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation 103 at index 0 writes parameter 1 in mode 1!")]
+    fn should_reject_immediate_input() {
+        run_operations(&parse("103,3,99,0"), 7);
+    }
+
+    #[test]
+    fn should_cover_diagnostic() {
+        // outputs whether the input is non-zero
+        let program = intcode::parse("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9");
+        let coverage = cover_diagnostic(&program);
+
+        assert_eq!(coverage.executed(), vec![0, 2, 5, 9, 11]);
+        assert_eq!(coverage.missing_branches(), vec![(2, true)]);
+    }
+
     #[test]
     fn should_solve_part_one() {
         let input: String = fs::read_to_string(PATH).unwrap();
@@ -1,26 +1,34 @@
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
 
 use itertools::Itertools;
 
+use crate::intcode::{network::Network, parse, IntcodeComputer, State};
+
 const PATH: &str = "src/day7.txt";
 
 pub fn solve() {
     let input: String = fs::read_to_string(PATH).unwrap();
+    let program = parse(&input);
+    let series_cache = AmplifierCache::new(&program);
+    let feedback_cache = AmplifierCache::new(&program);
 
     println!(
-        "Day 7: \n a) {} \n b) {}",
-        solve_part_one(&input),
-        solve_part_two(&input)
+        "Day 7: \n a) {} \n b) {} \n cache hit rates: {:.1}% / {:.1}%",
+        solve_part_one(&input, &series_cache),
+        solve_part_two(&input, &feedback_cache),
+        series_cache.hit_rate() * 100.0,
+        feedback_cache.hit_rate() * 100.0
     );
 }
 
-fn parse(input: &str) -> Vec<i64> {
-    input
-        .split(',')
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect()
-}
-
 fn extract_param(memory: &[i64], index: usize, immediate_mode: bool) -> i64 {
     match immediate_mode {
         true => memory[index],
@@ -28,10 +36,23 @@ fn extract_param(memory: &[i64], index: usize, immediate_mode: bool) -> i64 {
     }
 }
 
+/// Write parameters are always addresses, any other mode means the operation is broken.
+fn extract_target(memory: &[i64], index: usize, offset: usize, modes: &[char]) -> i64 {
+    let mode = modes[3 - offset];
+    if mode != '0' {
+        panic!(
+            "Operation {} at index {} writes parameter {} in mode {}!",
+            memory[index], index, offset, mode
+        );
+    }
+
+    memory[index + offset]
+}
+
 fn add(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -43,7 +64,7 @@ fn add(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
 fn multiply(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -89,7 +110,7 @@ fn jump_if_false(memory: &[i64], index: usize, modes: &[char]) -> OperationResul
 fn less_than(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -101,7 +122,7 @@ fn less_than(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
 fn equal_to(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
     let first_param = extract_param(memory, index + 1, modes[2] == '1');
     let second_param = extract_param(memory, index + 2, modes[1] == '1');
-    let third_param = extract_param(memory, index + 3, true);
+    let third_param = extract_target(memory, index, 3, modes);
 
     OperationResult {
         target_index: third_param,
@@ -110,8 +131,8 @@ fn equal_to(memory: &[i64], index: usize, modes: &[char]) -> OperationResult {
     }
 }
 
-fn set(memory: &[i64], index: usize, input: i64) -> OperationResult {
-    let first_param = extract_param(memory, index + 1, true);
+fn set(memory: &[i64], index: usize, modes: &[char], input: i64) -> OperationResult {
+    let first_param = extract_target(memory, index, 1, modes);
 
     OperationResult {
         target_index: first_param,
@@ -132,86 +153,33 @@ fn get(memory: &[i64], index: usize, modes: &[char], outputs: &mut Vec<i64>) ->
     }
 }
 
-struct IntcodeComputer {
-    memory: Vec<i64>,
-    running: bool,
-    index: usize,
-    initialized: bool,
+fn run_operations(memory: &[i64], phase_setting: i64, previous_amplifier_result: i64) -> Vec<i64> {
+    let mut memory = memory.to_vec();
+    run_program(&mut memory, phase_setting, previous_amplifier_result)
 }
 
-impl IntcodeComputer {
-    fn run_operations(
-        &mut self,
-        phase_setting: i64,
-        previous_amplifier_result: i64,
-        looping: bool,
-    ) -> Option<i64> {
-        let mut outputs: Vec<i64> = vec![];
-
-        loop {
-            let mut current: Vec<char> = self.memory[self.index].to_string().chars().collect();
-
-            while current.len() < 5 {
-                current.splice(0..0, ['0']);
-            }
-
-            let op_code: String = current.iter().rev().take(2).rev().collect();
-
-            let op_result = match op_code.as_str() {
-                "99" => {
-                    self.running = false;
-                    break;
-                }
-                "01" => add(&self.memory, self.index, &current),
-                "02" => multiply(&self.memory, self.index, &current),
-                "03" => {
-                    if !self.initialized {
-                        self.initialized = true;
-                        set(&self.memory, self.index, phase_setting)
-                    } else {
-                        set(&self.memory, self.index, previous_amplifier_result)
-                    }
-                }
-                "04" => {
-                    let op_result = get(&self.memory, self.index, &current, &mut outputs);
-
-                    if looping {
-                        self.index = op_result.steps;
-                        break;
-                    } else {
-                        op_result
-                    }
-                }
-                "05" => jump_if_true(&self.memory, self.index, &current),
-                "06" => jump_if_false(&self.memory, self.index, &current),
-                "07" => less_than(&self.memory, self.index, &current),
-                "08" => equal_to(&self.memory, self.index, &current),
-                _ => panic!("Operation code {} could not be run!", op_code),
-            };
-
-            if op_result.target_index != -1 {
-                self.memory[op_result.target_index as usize] = op_result.target_value;
-            }
-
-            self.index = op_result.steps;
-        }
-
-        outputs.last().copied()
-    }
+/// Runs the program in place, leaving its final state in `memory`.
+pub fn run_program(
+    memory: &mut [i64],
+    phase_setting: i64,
+    previous_amplifier_result: i64,
+) -> Vec<i64> {
+    run_program_for(memory, phase_setting, previous_amplifier_result, usize::MAX).unwrap()
 }
 
-fn run_operations(
-    memory: &[i64],
+/// Like `run_program`, but gives up after `max_steps` instructions and returns
+/// `None` if the program did not halt by then.
+pub fn run_program_for(
+    memory: &mut [i64],
     phase_setting: i64,
     previous_amplifier_result: i64,
-    looping: bool,
-) -> Vec<i64> {
-    let mut memory = memory.to_vec();
+    max_steps: usize,
+) -> Option<Vec<i64>> {
     let mut outputs: Vec<i64> = vec![];
     let mut index = 0;
     let mut initialized = false;
 
-    loop {
+    for _ in 0..max_steps {
         let mut current: Vec<char> = memory[index].to_string().chars().collect();
 
         while current.len() < 5 {
@@ -221,30 +189,22 @@ fn run_operations(
         let op_code: String = current.iter().rev().take(2).rev().collect();
 
         let op_result = match op_code.as_str() {
-            "99" => break,
-            "01" => add(&memory, index, &current),
-            "02" => multiply(&memory, index, &current),
+            "99" => return Some(outputs),
+            "01" => add(memory, index, &current),
+            "02" => multiply(memory, index, &current),
             "03" => {
                 if !initialized {
                     initialized = true;
-                    set(&memory, index, phase_setting)
+                    set(memory, index, &current, phase_setting)
                 } else {
-                    set(&memory, index, previous_amplifier_result)
+                    set(memory, index, &current, previous_amplifier_result)
                 }
             }
-            "04" => {
-                let op_result = get(&memory, index, &current, &mut outputs);
-
-                if looping {
-                    break;
-                } else {
-                    op_result
-                }
-            }
-            "05" => jump_if_true(&memory, index, &current),
-            "06" => jump_if_false(&memory, index, &current),
-            "07" => less_than(&memory, index, &current),
-            "08" => equal_to(&memory, index, &current),
+            "04" => get(memory, index, &current, &mut outputs),
+            "05" => jump_if_true(memory, index, &current),
+            "06" => jump_if_false(memory, index, &current),
+            "07" => less_than(memory, index, &current),
+            "08" => equal_to(memory, index, &current),
             _ => panic!("Operation code {} could not be run!", op_code),
         };
 
@@ -255,7 +215,7 @@ fn run_operations(
         index = op_result.steps;
     }
 
-    outputs
+    None
 }
 
 struct OperationResult {
@@ -264,112 +224,286 @@ struct OperationResult {
     steps: usize,
 }
 
-fn solve_part_one(input: &str) -> i64 {
+fn solve_part_one(input: &str, cache: &AmplifierCache) -> i64 {
     let input = parse(input);
 
-    let mut a_results: HashMap<Vec<i64>, i64> = HashMap::new();
-    let mut b_results: HashMap<Vec<i64>, i64> = HashMap::new();
-    let mut c_results: HashMap<Vec<i64>, i64> = HashMap::new();
-    let mut d_results: HashMap<Vec<i64>, i64> = HashMap::new();
-    let mut e_results: HashMap<Vec<i64>, i64> = HashMap::new();
+    find_best_phase_settings(
+        &input,
+        &[0, 1, 2, 3, 4],
+        5,
+        false,
+        available_threads(),
+        cache,
+    )
+    .signal
+}
 
-    for i in 0..=4 {
-        let a_result = *run_operations(&input, i, 0, false).last().unwrap();
-        a_results.insert(vec![i], a_result);
-    }
+fn solve_part_two(input: &str, cache: &AmplifierCache) -> i64 {
+    let input = parse(input);
 
-    for i in 0..=4 {
-        for a_result in a_results.iter().filter(|a| !a.0.contains(&i)) {
-            let b_result = *run_operations(&input, i, *a_result.1, false)
-                .last()
-                .unwrap();
+    find_best_phase_settings(
+        &input,
+        &[5, 6, 7, 8, 9],
+        5,
+        true,
+        available_threads(),
+        cache,
+    )
+    .signal
+}
 
-            let mut key = a_result.0.clone();
-            key.extend(vec![i]);
+#[derive(Debug, PartialEq)]
+struct PhaseSearchResult {
+    signal: i64,
+    phase_settings: Vec<i64>,
+}
 
-            b_results.insert(key, b_result);
+/// Tries every ordering of `amplifiers` distinct values out of `phases` and returns
+/// the highest signal together with the first phase settings producing it.
+///
+/// The orderings are split into contiguous chunks, one per thread, so the result
+/// does not depend on the number of threads.
+fn find_best_phase_settings(
+    program: &[i64],
+    phases: &[i64],
+    amplifiers: usize,
+    feedback: bool,
+    threads: usize,
+    cache: &AmplifierCache,
+) -> PhaseSearchResult {
+    let permutations: Vec<Vec<i64>> = phases.iter().copied().permutations(amplifiers).collect();
+    let chunk_size = permutations.len().div_ceil(threads.max(1)).max(1);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = permutations
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || find_best_in(program, chunk, feedback, cache)))
+            .collect();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .reduce(|best, next| {
+                if next.signal > best.signal {
+                    next
+                } else {
+                    best
+                }
+            })
+    })
+    .expect("Not enough phases for the amplifiers!")
+}
+
+fn find_best_in(
+    program: &[i64],
+    permutations: &[Vec<i64>],
+    feedback: bool,
+    cache: &AmplifierCache,
+) -> Option<PhaseSearchResult> {
+    let mut best: Option<PhaseSearchResult> = None;
+
+    for phase_settings in permutations {
+        let signal = run_amplifiers(program, phase_settings, feedback, cache);
+
+        if best.as_ref().is_none_or(|b| signal > b.signal) {
+            best = Some(PhaseSearchResult {
+                signal,
+                phase_settings: phase_settings.clone(),
+            });
         }
     }
 
-    for i in 0..=4 {
-        for b_result in b_results.iter().filter(|b| !b.0.contains(&i)) {
-            let c_result = *run_operations(&input, i, *b_result.1, false)
-                .last()
-                .unwrap();
+    best
+}
 
-            let mut key = b_result.0.clone();
-            key.extend(vec![i]);
+fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
 
-            c_results.insert(key, c_result);
-        }
+fn run_amplifiers(
+    program: &[i64],
+    phase_settings: &[i64],
+    feedback: bool,
+    cache: &AmplifierCache,
+) -> i64 {
+    assert!(
+        cache.program == program,
+        "Amplifier cache belongs to another program!"
+    );
+
+    if let Some(signal) = run_cached_amplifiers(phase_settings, feedback, cache) {
+        return signal;
+    }
+
+    if feedback {
+        return Network::parse(&amplifier_config(phase_settings, true))
+            .run(program)
+            .unwrap();
     }
 
-    for i in 0..=4 {
-        for c_result in c_results.iter().filter(|c| !c.0.contains(&i)) {
-            let d_result = *run_operations(&input, i, *c_result.1, false)
-                .last()
-                .unwrap();
+    phase_settings.iter().fold(0, |signal, phase_setting| {
+        *run_operations(program, *phase_setting, signal)
+            .last()
+            .unwrap()
+    })
+}
+
+fn run_cached_amplifiers(
+    phase_settings: &[i64],
+    feedback: bool,
+    cache: &AmplifierCache,
+) -> Option<i64> {
+    let mut amplifiers: Vec<IntcodeComputer> = vec![];
+    let mut signal = 0;
 
-            let mut key = c_result.0.clone();
-            key.extend(vec![i]);
+    for phase_setting in phase_settings {
+        let (amplifier, output) = cache.first_pass(*phase_setting, signal)?;
 
-            d_results.insert(key, d_result);
+        if !feedback && amplifier.state() != State::Halted {
+            cache.disable();
+            return None;
         }
+
+        amplifiers.push(amplifier);
+        signal = output;
     }
 
-    for i in 0..=4 {
-        for d_result in d_results.iter().filter(|d| !d.0.contains(&i)) {
-            let e_result = *run_operations(&input, i, *d_result.1, false)
-                .last()
-                .unwrap();
+    let mut signals = vec![signal];
 
-            let mut key = d_result.0.clone();
-            key.extend(vec![i]);
+    // mirrors `Network::run`, so both paths agree on every program
+    while feedback && amplifiers.iter().any(|a| a.state() != State::Halted) {
+        let mut progressed = false;
+        let last = amplifiers.len() - 1;
 
-            e_results.insert(key, e_result);
+        for (i, amplifier) in amplifiers.iter_mut().enumerate() {
+            signals.iter().for_each(|s| amplifier.push_input(*s));
+
+            if amplifier.state() == State::Halted
+                || amplifier.state() == State::WaitingForInput && !amplifier.has_pending_input()
+            {
+                // a halted amplifier drops its signals
+                signals.clear();
+                continue;
+            }
+
+            progressed = true;
+            amplifier.run();
+            signals = amplifier.take_outputs();
+
+            if let (true, Some(output)) = (i == last, signals.last()) {
+                signal = *output;
+            }
+        }
+
+        if !progressed {
+            panic!("Network is deadlocked, every running node waits for input!");
         }
     }
 
-    *e_results.values().max().unwrap()
+    Some(signal)
 }
 
-fn solve_part_two(input: &str) -> i64 {
-    let input = parse(input);
+/// Remembers amplifiers of one program after their first pass, keyed by phase setting
+/// and input signal.
+///
+/// Until an amplifier outputs its first signal it only depends on those two values,
+/// so phase orderings sharing a prefix can skip rerunning it. Programs that want a
+/// third input or output more than one signal on that first pass switch the cache off.
+struct AmplifierCache {
+    program: Vec<i64>,
+    passes: Mutex<HashMap<(i64, i64), (IntcodeComputer, i64)>>,
+    enabled: AtomicBool,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
 
-    let phase_settings = vec![5, 6, 7, 8, 9];
-    let permutations: Vec<Vec<i64>> = phase_settings.into_iter().permutations(5).collect();
+impl AmplifierCache {
+    fn new(program: &[i64]) -> AmplifierCache {
+        AmplifierCache {
+            program: program.to_vec(),
+            passes: Mutex::new(HashMap::new()),
+            enabled: AtomicBool::new(true),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
 
-    let mut max_result = 0;
+    fn first_pass(&self, phase_setting: i64, signal: i64) -> Option<(IntcodeComputer, i64)> {
+        if !self.is_enabled() {
+            return None;
+        }
 
-    for permutation in permutations {
-        let mut amps: Vec<IntcodeComputer> = (1..=5)
-            .map(|_| IntcodeComputer {
-                memory: input.clone(),
-                running: true,
-                initialized: false,
-                index: 0,
-            })
-            .collect();
+        let key = (phase_setting, signal);
 
-        let mut previous_amplifier_result = 0;
+        if let Some(pass) = self.passes.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(pass.clone());
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
 
-        while amps.iter().all(|a| a.running) {
-            for (i, amp) in amps.iter_mut().enumerate() {
-                let phase_setting = permutation[i];
+        let mut amplifier = IntcodeComputer::new(&self.program);
+        amplifier.push_input(phase_setting);
+        amplifier.push_input(signal);
+        amplifier.run();
 
-                match amp.run_operations(phase_setting, previous_amplifier_result, true) {
-                    Some(result) => previous_amplifier_result = result,
-                    None => break,
-                }
-            }
+        let outputs = amplifier.take_outputs();
+
+        if outputs.len() != 1 {
+            self.disable();
+            return None;
         }
 
-        if previous_amplifier_result > max_result {
-            max_result = previous_amplifier_result
+        let pass = (amplifier, outputs[0]);
+        self.passes.lock().unwrap().insert(key, pass.clone());
+
+        Some(pass)
+    }
+
+    fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn hit_rate(&self) -> f64 {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let lookups = hits + self.misses.load(Ordering::Relaxed);
+
+        if lookups == 0 {
+            return 0.0;
         }
+
+        hits as f64 / lookups as f64
+    }
+}
+
+fn amplifier_config(phase_settings: &[i64], feedback: bool) -> String {
+    let last = phase_settings.len() - 1;
+
+    let mut config = format!(
+        "nodes {}\nresult {}\ninput 0 {} 0\n",
+        last + 1,
+        last,
+        phase_settings[0]
+    );
+
+    for (i, phase_setting) in phase_settings.iter().enumerate().skip(1) {
+        config.push_str(&format!(
+            "input {} {}\nedge {} {}\n",
+            i,
+            phase_setting,
+            i - 1,
+            i
+        ));
+    }
+
+    if feedback {
+        config.push_str(&format!("edge {} 0\n", last));
     }
 
-    max_result
+    config
 }
 
 #[cfg(test)]
@@ -381,15 +515,214 @@ mod tests {
         assert_eq!(parse("1,0,0,3"), vec![1, 0, 0, 3]);
     }
 
+    #[test]
+    #[should_panic(expected = "Operation 11101 at index 0 writes parameter 3 in mode 1!")]
+    fn should_reject_immediate_writes() {
+        run_operations(&parse("11101,1,1,5,99,0"), 0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation 103 at index 0 writes parameter 1 in mode 1!")]
+    fn should_reject_immediate_input_writes() {
+        run_operations(&parse("103,3,99,0"), 0, 0);
+    }
+
+    #[test]
+    fn should_build_amplifier_config() {
+        assert_eq!(
+            amplifier_config(&[9, 8, 7], true),
+            "nodes 3\nresult 2\ninput 0 9 0\ninput 1 8\nedge 0 1\ninput 2 7\nedge 1 2\nedge 2 0\n"
+        );
+    }
+
+    #[test]
+    fn should_find_best_phase_settings_in_series() {
+        let program = parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+
+        assert_eq!(
+            find_best_phase_settings(
+                &program,
+                &[0, 1, 2, 3, 4],
+                5,
+                false,
+                1,
+                &AmplifierCache::new(&program)
+            ),
+            PhaseSearchResult {
+                signal: 43210,
+                phase_settings: vec![4, 3, 2, 1, 0],
+            }
+        );
+        assert_eq!(
+            find_best_phase_settings(
+                &program,
+                &[0, 1, 2, 3, 4],
+                3,
+                false,
+                4,
+                &AmplifierCache::new(&program)
+            ),
+            PhaseSearchResult {
+                signal: 432,
+                phase_settings: vec![4, 3, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn should_find_best_phase_settings_with_feedback() {
+        let program = parse(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        );
+
+        assert_eq!(
+            find_best_phase_settings(
+                &program,
+                &[5, 6, 7, 8, 9],
+                5,
+                true,
+                4,
+                &AmplifierCache::new(&program)
+            ),
+            PhaseSearchResult {
+                signal: 139629729,
+                phase_settings: vec![9, 8, 7, 6, 5],
+            }
+        );
+    }
+
+    #[test]
+    fn should_find_same_phase_settings_for_any_thread_count() {
+        // every amplifier ignores its phase and adds one, so all orderings tie
+        let program = parse("3,11,3,12,1001,12,1,12,4,12,99,0,0");
+        let phases = [0, 1, 2, 3, 4, 5, 6];
+        let expected = find_best_phase_settings(
+            &program,
+            &phases,
+            3,
+            false,
+            1,
+            &AmplifierCache::new(&program),
+        );
+
+        for threads in [2, 3, 7, 64] {
+            assert_eq!(
+                find_best_phase_settings(
+                    &program,
+                    &phases,
+                    3,
+                    false,
+                    threads,
+                    &AmplifierCache::new(&program)
+                ),
+                expected
+            );
+        }
+        assert_eq!(
+            expected,
+            PhaseSearchResult {
+                signal: 3,
+                phase_settings: vec![0, 1, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn should_reuse_cached_amplifiers() {
+        let program = parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+        let cache = AmplifierCache::new(&program);
+
+        let result = find_best_phase_settings(&program, &[0, 1, 2, 3, 4], 5, false, 1, &cache);
+
+        assert_eq!(result.signal, 43210);
+        // orderings starting with phase 0 pass on signals that other amplifiers already saw
+        assert_eq!(cache.misses.load(Ordering::Relaxed), 261);
+        assert_eq!(cache.hit_rate(), 339.0 / 600.0);
+    }
+
+    #[test]
+    fn should_cache_feedback_loops() {
+        let program = parse(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        );
+        let cache = AmplifierCache::new(&program);
+
+        assert_eq!(
+            run_amplifiers(&program, &[9, 8, 7, 6, 5], true, &cache),
+            139629729
+        );
+        assert_eq!(
+            run_amplifiers(&program, &[9, 8, 7, 6, 5], true, &cache),
+            139629729
+        );
+        assert!(cache.is_enabled());
+        assert_eq!(cache.hit_rate(), 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "deadlocked")]
+    fn should_detect_deadlock_in_cached_feedback_loop() {
+        let program = parse("3,100,3,101,4,101,3,101,99");
+
+        run_amplifiers(&program, &[5, 6], true, &AmplifierCache::new(&program));
+    }
+
+    #[test]
+    #[should_panic(expected = "deadlocked")]
+    fn should_drop_signals_of_halted_amplifiers() {
+        // adds the phase to every signal, phase 5 halts after the first one
+        let program = parse("3,100,3,101,1,100,101,102,4,102,1008,100,5,103,1006,103,2,99");
+
+        run_amplifiers(&program, &[5, 6], true, &AmplifierCache::new(&program));
+    }
+
+    #[test]
+    #[should_panic(expected = "deadlocked")]
+    fn should_drop_signals_of_halted_amplifiers_without_cache() {
+        let program = parse("3,100,3,101,1,100,101,102,4,102,1008,100,5,103,1006,103,2,99");
+        let cache = AmplifierCache::new(&program);
+        cache.disable();
+
+        run_amplifiers(&program, &[5, 6], true, &cache);
+    }
+
+    #[test]
+    #[should_panic(expected = "another program")]
+    fn should_reject_cache_of_another_program() {
+        let program = parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+        let cache = AmplifierCache::new(&program);
+        run_amplifiers(&program, &[4, 3], false, &cache);
+
+        // multiplies by 100 instead, which the cached passes know nothing of
+        let other = parse("3,15,3,16,1002,16,100,16,1,16,15,15,4,15,99,0,0");
+        run_amplifiers(&other, &[4, 3], false, &cache);
+    }
+
+    #[test]
+    fn should_disable_cache_for_programs_reading_more_inputs() {
+        // adds phase setting and the signal read twice
+        let program = parse("3,17,3,18,1,17,18,17,3,18,1,17,18,17,4,17,99,0,0");
+        let cache = AmplifierCache::new(&program);
+
+        assert_eq!(run_amplifiers(&program, &[1, 2], false, &cache), 4);
+        assert!(!cache.is_enabled());
+    }
+
     #[test]
     fn should_solve_part_one() {
         let input: String = fs::read_to_string(PATH).unwrap();
-        assert_eq!(solve_part_one(&input), 17406);
+        assert_eq!(
+            solve_part_one(&input, &AmplifierCache::new(&parse(&input))),
+            17406
+        );
     }
 
     #[test]
     fn should_solve_part_two() {
         let input: String = fs::read_to_string(PATH).unwrap();
-        assert_eq!(solve_part_two(&input), 1047153);
+        assert_eq!(
+            solve_part_two(&input, &AmplifierCache::new(&parse(&input))),
+            1047153
+        );
     }
 }
@@ -2,6 +2,9 @@ use std::fs;
 
 const PATH: &str = "src/day8.txt";
 
+pub const WHITE: char = '⬜';
+pub const BLACK: char = '⬛';
+
 pub fn solve() {
     let input: String = fs::read_to_string(PATH).unwrap();
     println!(
@@ -58,8 +61,8 @@ fn decode(layers: &[Layer], length: usize) -> String {
         for layer in layers {
             match layer.pixels[i] {
                 '2' => continue,
-                '1' => first_non_transparent_pixels.push('⬜'),
-                '0' => first_non_transparent_pixels.push('⬛'),
+                '1' => first_non_transparent_pixels.push(WHITE),
+                '0' => first_non_transparent_pixels.push(BLACK),
                 _ => panic!("Pixel contains invalid color!"),
             }
             break;
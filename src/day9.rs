@@ -1,5 +1,7 @@
 use std::{collections::HashMap, fs};
 
+use crate::intcode;
+
 const PATH: &str = "src/day9.txt";
 
 pub fn solve() {
@@ -13,11 +15,7 @@ pub fn solve() {
 }
 
 fn parse(input: &str) -> HashMap<usize, i64> {
-    input
-        .split(',')
-        .map(|s| s.parse::<i64>().unwrap())
-        .enumerate()
-        .collect()
+    intcode::parse(input).into_iter().enumerate().collect()
 }
 
 struct IntcodeComputer {
@@ -34,9 +32,15 @@ impl IntcodeComputer {
         if literal {
             match mode {
                 '0' => *self.memory.get(&(index as usize)).unwrap_or(&0),
-                '1' => *self.memory.get(&(index as usize)).unwrap_or(&0),
                 '2' => *self.memory.get(&(index as usize)).unwrap_or(&0) + self.relative_base,
-                _ => panic!("Literal Param could not be extracted"),
+                // write parameters are always addresses
+                _ => panic!(
+                    "Operation {} at index {} writes parameter {} in mode {}!",
+                    self.memory[&self.index],
+                    self.index,
+                    index - self.index as i64,
+                    mode
+                ),
             }
         } else {
             match mode {
@@ -170,7 +174,7 @@ impl IntcodeComputer {
     fn adjust_base(&mut self, modes: &[char]) -> OperationResult {
         let first_param = self.extract_param(self.index as i64 + 1, modes[2], false);
 
-        self.relative_base = self.relative_base + first_param;
+        self.relative_base += first_param;
 
         OperationResult {
             target_index: -1,
@@ -179,13 +183,13 @@ impl IntcodeComputer {
         }
     }
 
-    fn run_operations(&mut self, input_value: i64) -> Vec<i64> {
+    fn run_operations(&mut self, input_value: i64, max_steps: usize) -> Option<Vec<i64>> {
         let mut outputs: Vec<i64> = vec![];
 
-        loop {
+        for _ in 0..max_steps {
             let mut current: Vec<char> = self
                 .memory
-                .get(&(self.index as usize))
+                .get(&self.index)
                 .unwrap()
                 .to_string()
                 .chars()
@@ -200,7 +204,7 @@ impl IntcodeComputer {
             let op_result = match op_code.as_str() {
                 "99" => {
                     self.running = false;
-                    break;
+                    return Some(outputs);
                 }
                 "01" => self.add(&current),
                 "02" => self.multiply(&current),
@@ -222,7 +226,7 @@ impl IntcodeComputer {
             self.index = op_result.steps;
         }
 
-        outputs.clone()
+        None
     }
 }
 
@@ -233,33 +237,41 @@ struct OperationResult {
 }
 
 fn solve_part_one(input: &str) -> i64 {
-    let input = parse(input);
+    let (result, _) = run_program(parse(input), 1);
 
-    let mut intcode_computer: IntcodeComputer = IntcodeComputer {
-        memory: input,
-        running: true,
-        index: 0,
-        relative_base: 0,
-    };
+    *result.last().unwrap()
+}
 
-    let result = intcode_computer.run_operations(1);
+fn solve_part_two(input: &str) -> i64 {
+    let (result, _) = run_program(parse(input), 2);
 
     *result.last().unwrap()
 }
 
-fn solve_part_two(input: &str) -> i64 {
-    let input = parse(input);
+/// Runs the program until it halts and returns its outputs and final memory.
+pub fn run_program(memory: HashMap<usize, i64>, input: i64) -> (Vec<i64>, HashMap<usize, i64>) {
+    let (result, memory) = run_program_for(memory, input, usize::MAX);
 
+    (result.unwrap(), memory)
+}
+
+/// Like `run_program`, but gives up after `max_steps` instructions and returns
+/// no outputs if the program did not halt by then.
+pub fn run_program_for(
+    memory: HashMap<usize, i64>,
+    input: i64,
+    max_steps: usize,
+) -> (Option<Vec<i64>>, HashMap<usize, i64>) {
     let mut intcode_computer: IntcodeComputer = IntcodeComputer {
-        memory: input,
+        memory,
         running: true,
         index: 0,
         relative_base: 0,
     };
 
-    let result = intcode_computer.run_operations(2);
+    let result = intcode_computer.run_operations(input, max_steps);
 
-    *result.last().unwrap()
+    (result, intcode_computer.memory)
 }
 
 #[cfg(test)]
@@ -289,6 +301,12 @@ mod tests {
         assert_eq!(solve_part_one("109,1,203,11,209,8,204,1,99,10,0,42,0"), 1);
     }
 
+    #[test]
+    #[should_panic(expected = "Operation 11101 at index 2 writes parameter 3 in mode 1!")]
+    fn should_reject_immediate_writes() {
+        run_program(parse("109,1,11101,1,1,9,99"), 0);
+    }
+
     #[test]
     fn should_solve_part_one() {
         let input: String = fs::read_to_string(PATH).unwrap();
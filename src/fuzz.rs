@@ -0,0 +1,455 @@
+//! Differential fuzzing of every Intcode interpreter in the crate.
+//!
+//! Generated programs jump to any of their instructions, so they can loop
+//! forever. Every interpreter runs under the same step budget and running out
+//! of it is an outcome like any other. Day2's interpreter only knows additions
+//! and multiplications, which always end, so it only joins in for programs made
+//! of nothing else.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    day2, day5, day7, day9,
+    intcode::{IntcodeComputer, State},
+};
+
+/// Cells after the code that programs read from and write to.
+const DATA_CELLS: usize = 8;
+
+/// Instructions, the final halt included, a program may run.
+const MAX_STEPS: usize = 1_000;
+
+pub struct Options {
+    /// Maximum number of instructions before the final halt.
+    pub instructions: usize,
+    /// Generate relative mode parameters, writes included, and base
+    /// adjustments, which only some interpreters support.
+    pub relative_mode: bool,
+    /// Let parameters address memory past the end of the program.
+    pub past_end: bool,
+    /// Only generate additions and multiplications in position mode, like the
+    /// gravity assist program, and fuzz day2's interpreter as well.
+    pub arithmetic_only: bool,
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Finished { outputs: Vec<i64>, memory: Vec<i64> },
+    Exhausted,
+    Failed(String),
+}
+
+impl Outcome {
+    /// Failures agree with each other whatever their message is.
+    fn agrees_with(&self, other: &Outcome) -> bool {
+        match (self, other) {
+            (Outcome::Exhausted, Outcome::Exhausted) => true,
+            (Outcome::Failed(_), Outcome::Failed(_)) => true,
+            (
+                Outcome::Finished { outputs, memory },
+                Outcome::Finished {
+                    outputs: other_outputs,
+                    memory: other_memory,
+                },
+            ) => outputs == other_outputs && memory == other_memory,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Finished { outputs, memory } => {
+                write!(f, "outputs {:?}, memory {:?}", outputs, memory)
+            }
+            Outcome::Exhausted => write!(f, "ran for {} steps without halting", MAX_STEPS),
+            Outcome::Failed(message) => write!(f, "failed with {}", message),
+        }
+    }
+}
+
+pub struct Mismatch {
+    pub program: Vec<i64>,
+    pub input: i64,
+    pub outcomes: Vec<(&'static str, Outcome)>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let program: Vec<String> = self.program.iter().map(|v| v.to_string()).collect();
+
+        writeln!(f, "program: {}", program.join(","))?;
+        writeln!(f, "input: {}", self.input)?;
+        for (name, outcome) in &self.outcomes {
+            writeln!(f, "{}: {}", name, outcome)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a program feeding `input` to every read and returns outputs and final
+/// memory, or `None` if it did not halt within `MAX_STEPS`.
+type Interpreter = fn(&[i64], i64) -> Option<(Vec<i64>, Vec<i64>)>;
+
+const DAY2: (&str, Interpreter) = ("day2", run_day2);
+
+const INTERPRETERS: [(&str, Interpreter); 4] = [
+    ("day5", run_day5),
+    ("day7", run_day7),
+    ("day9", run_day9),
+    ("intcode", run_intcode),
+];
+
+fn run_day2(program: &[i64], _: i64) -> Option<(Vec<i64>, Vec<i64>)> {
+    let mut memory: Vec<i32> = program.iter().map(|v| *v as i32).collect();
+    day2::run_operations(&mut memory);
+
+    Some((vec![], memory.into_iter().map(i64::from).collect()))
+}
+
+fn run_day5(program: &[i64], input: i64) -> Option<(Vec<i64>, Vec<i64>)> {
+    let mut memory: Vec<i32> = program.iter().map(|v| *v as i32).collect();
+    let outputs = day5::run_program_for(&mut memory, input as i32, MAX_STEPS)?;
+
+    Some((
+        outputs.into_iter().map(i64::from).collect(),
+        memory.into_iter().map(i64::from).collect(),
+    ))
+}
+
+fn run_day7(program: &[i64], input: i64) -> Option<(Vec<i64>, Vec<i64>)> {
+    let mut memory = program.to_vec();
+    let outputs = day7::run_program_for(&mut memory, input, input, MAX_STEPS)?;
+
+    Some((outputs, memory))
+}
+
+fn run_day9(program: &[i64], input: i64) -> Option<(Vec<i64>, Vec<i64>)> {
+    let (outputs, cells) = day9::run_program_for(
+        program.iter().copied().enumerate().collect(),
+        input,
+        MAX_STEPS,
+    );
+
+    Some((outputs?, to_memory(&cells)))
+}
+
+fn run_intcode(program: &[i64], input: i64) -> Option<(Vec<i64>, Vec<i64>)> {
+    let mut computer = IntcodeComputer::new(program);
+    // every step reads at most one input
+    (0..MAX_STEPS).for_each(|_| computer.push_input(input));
+
+    match computer.run_for(MAX_STEPS) {
+        State::Halted => Some((computer.take_outputs(), computer.memory().to_vec())),
+        _ => None,
+    }
+}
+
+fn to_memory(cells: &HashMap<usize, i64>) -> Vec<i64> {
+    let length = cells.keys().max().map_or(0, |k| k + 1);
+
+    (0..length).map(|i| *cells.get(&i).unwrap_or(&0)).collect()
+}
+
+/// Runs `interpreter` and catches its panics, memory is compared without the
+/// trailing zeros some interpreters grow it by.
+fn execute(interpreter: Interpreter, program: &[i64], input: i64) -> Outcome {
+    match panic::catch_unwind(AssertUnwindSafe(|| interpreter(program, input))) {
+        Ok(Some((outputs, mut memory))) => {
+            while memory.last() == Some(&0) {
+                memory.pop();
+            }
+            Outcome::Finished { outputs, memory }
+        }
+        Ok(None) => Outcome::Exhausted,
+        Err(payload) => Outcome::Failed(
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Generates `rounds` programs from `seed` and returns the first one the
+/// interpreters disagree on.
+pub fn fuzz(rounds: usize, seed: u64, options: &Options) -> Option<Mismatch> {
+    let mut random = Random(seed.max(1));
+    let interpreters: Vec<(&str, Interpreter)> = options
+        .arithmetic_only
+        .then_some(DAY2)
+        .into_iter()
+        .chain(INTERPRETERS)
+        .collect();
+
+    for _ in 0..rounds {
+        let program = generate(&mut random, options);
+        let input = random.value();
+
+        let outcomes: Vec<(&'static str, Outcome)> = interpreters
+            .iter()
+            .map(|(name, interpreter)| (*name, execute(*interpreter, &program, input)))
+            .collect();
+
+        if outcomes.windows(2).any(|w| !w[0].1.agrees_with(&w[1].1)) {
+            return Some(Mismatch {
+                program,
+                input,
+                outcomes,
+            });
+        }
+    }
+
+    None
+}
+
+/// xorshift64*, good enough to spread programs and reproducible from its seed.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn value(&mut self) -> i64 {
+        self.below(21) as i64 - 10
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+}
+
+fn parameter_count(op_code: i64) -> usize {
+    match op_code {
+        1 | 2 | 7 | 8 => 3,
+        5 | 6 => 2,
+        3 | 4 | 9 => 1,
+        _ => 0,
+    }
+}
+
+fn generate(random: &mut Random, options: &Options) -> Vec<i64> {
+    let mut op_codes: Vec<i64> = if options.arithmetic_only {
+        vec![1, 2]
+    } else {
+        vec![1, 2, 3, 4, 5, 6, 7, 8]
+    };
+    if options.relative_mode {
+        op_codes.push(9);
+    }
+
+    let count = 1 + random.below(options.instructions);
+    let instructions: Vec<i64> = (0..count)
+        .map(|_| op_codes[random.below(op_codes.len())])
+        .collect();
+
+    let mut starts = vec![];
+    let mut length = 0;
+    for op_code in &instructions {
+        starts.push(length);
+        length += 1 + parameter_count(*op_code);
+    }
+    // the final halt
+    starts.push(length);
+
+    let data = length + 1;
+    let end = data + DATA_CELLS;
+
+    let mut program = vec![];
+
+    for op_code in &instructions {
+        let mut modes = vec![];
+        let mut params = vec![];
+
+        match op_code {
+            1 | 2 | 7 | 8 => {
+                for _ in 0..2 {
+                    let (mode, param) = read_param(random, options, data, end);
+                    modes.push(mode);
+                    params.push(param);
+                }
+                let (mode, param) = write_param(random, options, data, end);
+                modes.push(mode);
+                params.push(param);
+            }
+            3 => {
+                let (mode, param) = write_param(random, options, data, end);
+                modes.push(mode);
+                params.push(param);
+            }
+            4 | 9 => {
+                let (mode, param) = read_param(random, options, data, end);
+                modes.push(mode);
+                params.push(param);
+            }
+            _ => {
+                let (mode, param) = read_param(random, options, data, end);
+                modes.push(mode);
+                params.push(param);
+
+                let target = starts[random.below(starts.len())];
+                modes.push(1);
+                params.push(target as i64);
+            }
+        }
+
+        let modes = modes
+            .iter()
+            .enumerate()
+            .map(|(n, mode)| mode * 10_i64.pow(n as u32 + 2))
+            .sum::<i64>();
+
+        program.push(op_code + modes);
+        program.extend(params);
+    }
+
+    program.push(99);
+    program.extend((0..DATA_CELLS).map(|_| random.value()));
+
+    program
+}
+
+fn read_param(random: &mut Random, options: &Options, data: usize, end: usize) -> (i64, i64) {
+    if !options.arithmetic_only && random.chance(30) {
+        return (1, random.value());
+    }
+
+    if options.relative_mode && random.chance(20) {
+        return (2, random.value());
+    }
+
+    write_param(random, options, data, end)
+}
+
+/// Writes in position mode only ever go to the data cells (or past the end),
+/// in relative mode they go wherever the base points, code included.
+fn write_param(random: &mut Random, options: &Options, data: usize, end: usize) -> (i64, i64) {
+    if options.relative_mode && random.chance(20) {
+        return (2, random.value());
+    }
+
+    let address = if options.past_end && random.chance(10) {
+        end + random.below(DATA_CELLS)
+    } else {
+        data + random.below(DATA_CELLS)
+    };
+
+    (0, address as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMON: Options = Options {
+        instructions: 12,
+        relative_mode: false,
+        past_end: false,
+        arithmetic_only: false,
+    };
+
+    #[test]
+    fn should_generate_reproducible_programs() {
+        let first = generate(&mut Random(7), &COMMON);
+
+        assert_eq!(first, generate(&mut Random(7), &COMMON));
+        assert_ne!(first, generate(&mut Random(8), &COMMON));
+    }
+
+    #[test]
+    fn should_agree_on_common_features() {
+        if let Some(mismatch) = fuzz(2000, 1, &COMMON) {
+            panic!("Interpreters disagree:\n{}", mismatch);
+        }
+    }
+
+    #[test]
+    fn should_agree_on_arithmetic() {
+        // three multiplications of values up to 10 still fit into day2's i32 cells
+        let options = Options {
+            instructions: 3,
+            arithmetic_only: true,
+            ..COMMON
+        };
+
+        let program = generate(&mut Random(1), &options);
+        assert!(program
+            .chunks(4)
+            .take_while(|c| c[0] != 99)
+            .all(|c| c[0] == 1 || c[0] == 2));
+
+        if let Some(mismatch) = fuzz(2000, 1, &options) {
+            panic!("Interpreters disagree:\n{}", mismatch);
+        }
+    }
+
+    #[test]
+    fn should_find_overflowing_arithmetic() {
+        let options = Options {
+            arithmetic_only: true,
+            ..COMMON
+        };
+
+        let mismatch = fuzz(2000, 1, &options).expect("No mismatch found!");
+
+        assert!(matches!(mismatch.outcomes[0], ("day2", Outcome::Failed(_))));
+    }
+
+    #[test]
+    fn should_agree_on_endless_loops() {
+        // jumps back to itself forever
+        let program = [1105, 1, 0, 99];
+
+        for (name, interpreter) in INTERPRETERS {
+            let outcome = execute(interpreter, &program, 0);
+            assert!(
+                matches!(outcome, Outcome::Exhausted),
+                "{}: {}",
+                name,
+                outcome
+            );
+        }
+    }
+
+    #[test]
+    fn should_find_reads_past_the_end() {
+        let options = Options {
+            past_end: true,
+            ..COMMON
+        };
+
+        let mismatch = fuzz(2000, 1, &options).expect("No mismatch found!");
+
+        assert!(mismatch
+            .outcomes
+            .iter()
+            .any(|(_, outcome)| matches!(outcome, Outcome::Failed(_))));
+    }
+
+    #[test]
+    fn should_find_missing_relative_mode() {
+        let options = Options {
+            relative_mode: true,
+            ..COMMON
+        };
+
+        let mismatch = fuzz(2000, 1, &options).expect("No mismatch found!");
+
+        assert!(matches!(mismatch.outcomes[0], ("day5", Outcome::Failed(_))));
+    }
+}
@@ -0,0 +1,442 @@
+use std::{collections::VecDeque, fmt};
+
+use session::Direction;
+
+pub mod ascii;
+pub mod binary;
+#[cfg(test)]
+mod conformance;
+pub mod coverage;
+pub mod device;
+pub mod disassembly;
+pub mod dump;
+pub mod network;
+pub mod observer;
+pub mod optimize;
+pub mod session;
+pub mod transpile;
+
+/// Parses a program, panicking with the position of the first bad token.
+pub fn parse(input: &str) -> Vec<i64> {
+    load(input).unwrap_or_else(|e| panic!("{}", e))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Byte offset of the token in the input.
+    pub offset: usize,
+    /// Position of the token in the program.
+    pub index: usize,
+    pub token: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.token.is_empty() {
+            write!(
+                f,
+                "Value {} at byte {} is missing!",
+                self.index, self.offset
+            )
+        } else {
+            write!(
+                f,
+                "Value {} at byte {} is not a number: {}",
+                self.index, self.offset, self.token
+            )
+        }
+    }
+}
+
+/// Loads a comma separated program, ignoring whitespace around values and `#`
+/// comments up to the end of a line.
+pub fn load(input: &str) -> Result<Vec<i64>, ParseError> {
+    // blank out comments so offsets still point into the original input
+    let text: String = input
+        .split_inclusive('\n')
+        .map(|line| match line.find('#') {
+            Some(start) => {
+                let comment = &line[start..];
+                let blank = " ".repeat(comment.trim_end_matches('\n').len());
+                format!("{}{}{}", &line[..start], blank, &comment[blank.len()..])
+            }
+            None => line.to_string(),
+        })
+        .collect();
+
+    let mut offset = 0;
+    let mut program = vec![];
+
+    for (index, raw) in text.split(',').enumerate() {
+        let token = raw.trim();
+        let start = offset + (raw.len() - raw.trim_start().len());
+        offset += raw.len() + 1;
+
+        match token.parse::<i64>() {
+            Ok(value) => program.push(value),
+            Err(_) => {
+                return Err(ParseError {
+                    offset: start,
+                    index,
+                    token: token.to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Running,
+    WaitingForInput,
+    Halted,
+}
+
+#[derive(Clone)]
+pub struct IntcodeComputer {
+    memory: Vec<i64>,
+    index: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+    outputs: VecDeque<i64>,
+    state: State,
+    devices: Vec<device::Mapping>,
+    /// Instructions executed so far.
+    steps: usize,
+    session: Option<session::Session>,
+    observers: Vec<observer::Attached>,
+}
+
+impl IntcodeComputer {
+    pub fn new(program: &[i64]) -> IntcodeComputer {
+        IntcodeComputer {
+            memory: program.to_vec(),
+            index: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            state: State::Running,
+            devices: vec![],
+            steps: 0,
+            session: None,
+            observers: vec![],
+        }
+    }
+
+    /// Continues a program that was run elsewhere up to `index`. Only the native
+    /// code from `transpile` calls it, once the program modifies itself.
+    #[allow(dead_code)]
+    pub fn resume(memory: &[i64], index: usize, relative_base: i64) -> IntcodeComputer {
+        IntcodeComputer {
+            index,
+            relative_base,
+            ..IntcodeComputer::new(memory)
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Only the tests and the fuzzer look inside a computer.
+    #[cfg(test)]
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn has_pending_input(&self) -> bool {
+        !self.inputs.is_empty()
+    }
+
+    pub fn take_outputs(&mut self) -> Vec<i64> {
+        self.outputs.drain(..).collect()
+    }
+
+    /// Runs until the program halts or needs an input that has not been pushed yet.
+    pub fn run(&mut self) -> State {
+        if self.state == State::WaitingForInput && self.has_pending_input() {
+            self.state = State::Running;
+        }
+
+        while self.state == State::Running {
+            self.step();
+        }
+
+        self.state
+    }
+
+    /// Like `run`, but gives up after `max_steps` instructions and returns
+    /// `State::Running` if the program did not stop by then.
+    pub fn run_for(&mut self, max_steps: usize) -> State {
+        if self.state == State::WaitingForInput && self.has_pending_input() {
+            self.state = State::Running;
+        }
+
+        for _ in 0..max_steps {
+            if self.state != State::Running {
+                break;
+            }
+            self.step();
+        }
+
+        self.state
+    }
+
+    pub fn step(&mut self) -> State {
+        let instruction = self.read(self.index);
+
+        // waiting for input does not count as fetching the instruction
+        if !self.observers.is_empty() && (instruction % 100 != 3 || self.has_pending_input()) {
+            let index = self.index;
+            self.observe(|o| o.fetch(index, instruction));
+        }
+
+        match instruction % 100 {
+            1 => {
+                let value = self.param(1) + self.param(2);
+                self.write_param(3, value);
+                self.index += 4;
+            }
+            2 => {
+                let value = self.param(1) * self.param(2);
+                self.write_param(3, value);
+                self.index += 4;
+            }
+            3 => match self.inputs.pop_front() {
+                Some(value) => {
+                    self.record(Direction::In, value);
+                    self.observe(|o| o.input(value));
+                    self.write_param(1, value);
+                    self.index += 2;
+                }
+                None => {
+                    let index = self.index;
+                    self.observe(|o| o.wait(index));
+                    self.state = State::WaitingForInput;
+                    return self.state;
+                }
+            },
+            4 => {
+                let value = self.param(1);
+                self.record(Direction::Out, value);
+                self.observe(|o| o.output(value));
+                self.outputs.push_back(value);
+                self.index += 2;
+            }
+            5 => {
+                if self.param(1) != 0 {
+                    self.jump(self.param(2) as usize);
+                } else {
+                    self.index += 3;
+                }
+            }
+            6 => {
+                if self.param(1) == 0 {
+                    self.jump(self.param(2) as usize);
+                } else {
+                    self.index += 3;
+                }
+            }
+            7 => {
+                let value = i64::from(self.param(1) < self.param(2));
+                self.write_param(3, value);
+                self.index += 4;
+            }
+            8 => {
+                let value = i64::from(self.param(1) == self.param(2));
+                self.write_param(3, value);
+                self.index += 4;
+            }
+            9 => {
+                self.relative_base += self.param(1);
+                self.index += 2;
+            }
+            99 => {
+                self.state = State::Halted;
+                return self.state;
+            }
+            _ => panic!("Operation code {} could not be run!", instruction),
+        }
+
+        self.steps += 1;
+        self.state = State::Running;
+        self.state
+    }
+
+    fn read(&self, address: usize) -> i64 {
+        // a single check keeps memory without devices as fast as before
+        if !self.devices.is_empty() {
+            if let Some(value) = self.read_device(address) {
+                return value;
+            }
+        }
+
+        *self.memory.get(address).unwrap_or(&0)
+    }
+
+    fn write(&mut self, address: usize, value: i64) {
+        self.observe(|o| o.write(address, value));
+
+        if !self.devices.is_empty() && self.write_device(address, value) {
+            return;
+        }
+
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    fn mode(&self, offset: usize) -> i64 {
+        self.read(self.index) / 10_i64.pow(offset as u32 + 1) % 10
+    }
+
+    fn jump(&mut self, target: usize) {
+        let from = self.index;
+        self.observe(|o| o.jump(from, target));
+        self.index = target;
+    }
+
+    fn param(&self, offset: usize) -> i64 {
+        let raw = self.read(self.index + offset);
+
+        let address = match self.mode(offset) {
+            0 => to_address(raw),
+            1 => return raw,
+            2 => to_address(self.relative_base + raw),
+            mode => panic!("Parameter mode {} could not be read!", mode),
+        };
+
+        let value = self.read(address);
+        self.observe(|o| o.read(address, value));
+        value
+    }
+
+    fn write_param(&mut self, offset: usize, value: i64) {
+        let raw = self.read(self.index + offset);
+
+        let address = match self.mode(offset) {
+            0 => to_address(raw),
+            2 => to_address(self.relative_base + raw),
+            mode => panic!(
+                "Operation {} at index {} writes parameter {} in mode {}!",
+                self.read(self.index),
+                self.index,
+                offset,
+                mode
+            ),
+        };
+
+        self.write(address, value);
+    }
+}
+
+fn to_address(value: i64) -> usize {
+    usize::try_from(value).unwrap_or_else(|_| panic!("Address {} is negative!", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_program(program: &str, inputs: &[i64]) -> Vec<i64> {
+        let mut computer = IntcodeComputer::new(&parse(program));
+        inputs.iter().for_each(|i| computer.push_input(*i));
+        computer.run();
+        computer.take_outputs()
+    }
+
+    #[test]
+    fn should_parse() {
+        assert_eq!(parse("1,0,0,3"), vec![1, 0, 0, 3]);
+    }
+
+    #[test]
+    fn should_load_with_whitespace_and_comments() {
+        let input = "# adds two numbers\n1, 0,0,\n 3, # store\n99\n";
+
+        assert_eq!(load(input), Ok(vec![1, 0, 0, 3, 99]));
+        assert_eq!(parse("1,0,0,3\n"), vec![1, 0, 0, 3]);
+    }
+
+    #[test]
+    fn should_locate_bad_tokens() {
+        assert_eq!(
+            load("1,0, x2,3"),
+            Err(ParseError {
+                offset: 5,
+                index: 2,
+                token: String::from("x2"),
+            })
+        );
+        assert_eq!(
+            load("1,2,\n").unwrap_err().to_string(),
+            "Value 2 at byte 5 is missing!"
+        );
+        assert_eq!(
+            load("").unwrap_err().to_string(),
+            "Value 0 at byte 0 is missing!"
+        );
+    }
+
+    #[test]
+    fn should_compare_inputs() {
+        let program = "3,9,8,9,10,9,4,9,99,-1,8";
+
+        assert_eq!(run_program(program, &[8]), vec![1]);
+        assert_eq!(run_program(program, &[7]), vec![0]);
+    }
+
+    #[test]
+    fn should_wait_for_input() {
+        let mut computer = IntcodeComputer::new(&parse("3,0,4,0,99"));
+
+        assert_eq!(computer.run(), State::WaitingForInput);
+
+        computer.push_input(42);
+
+        assert_eq!(computer.run(), State::Halted);
+        assert_eq!(computer.take_outputs(), vec![42]);
+    }
+
+    #[test]
+    fn should_write_relative() {
+        // moves the base to 10 and stores the input at 10 + 1
+        let mut computer = IntcodeComputer::new(&parse("109,10,203,1,204,1,99"));
+        computer.push_input(5);
+        computer.run();
+
+        assert_eq!(computer.take_outputs(), vec![5]);
+        assert_eq!(computer.memory()[11], 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation 11101 at index 2 writes parameter 3 in mode 1!")]
+    fn should_reject_immediate_writes() {
+        run_program("104,1,11101,1,1,0,99", &[]);
+    }
+
+    #[test]
+    fn should_stop_after_step_budget() {
+        let mut computer = IntcodeComputer::new(&parse("1105,1,0"));
+
+        assert_eq!(computer.run_for(100), State::Running);
+    }
+
+    #[test]
+    fn should_run_day9_programs() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+        assert_eq!(run_program(quine, &[]), parse(quine));
+        assert_eq!(
+            run_program("1102,34915192,34915192,7,4,7,99,0", &[]),
+            vec![1219070632396864]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::{BufRead, Write};
+
+use super::{IntcodeComputer, State};
+
+/// Output of an ASCII program split into its text and every value outside the
+/// ASCII range, like a final answer printed after the text.
+#[derive(Debug, Default, PartialEq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<i64>,
+}
+
+impl IntcodeComputer {
+    /// Sends `line` as ASCII codes followed by a newline.
+    pub fn push_line(&mut self, line: &str) {
+        line.bytes().for_each(|b| self.push_input(i64::from(b)));
+        self.push_input(i64::from(b'\n'));
+    }
+
+    pub fn take_ascii_output(&mut self) -> AsciiOutput {
+        let mut output = AsciiOutput::default();
+
+        for value in self.take_outputs() {
+            match u8::try_from(value) {
+                Ok(byte) if byte.is_ascii() => output.text.push(char::from(byte)),
+                _ => output.values.push(value),
+            }
+        }
+
+        output
+    }
+
+    /// Runs a line based session: text the program prints goes to `output`, and
+    /// whenever it waits for input the next line of `input` is sent. Stops when the
+    /// program halts or `input` runs out and returns all non-ASCII values.
+    pub fn run_interactive(&mut self, mut input: impl BufRead, mut output: impl Write) -> Vec<i64> {
+        let mut values = vec![];
+
+        loop {
+            let state = self.run();
+            let ascii = self.take_ascii_output();
+
+            output.write_all(ascii.text.as_bytes()).unwrap();
+            output.flush().unwrap();
+            values.extend(ascii.values);
+
+            if state == State::Halted {
+                break;
+            }
+
+            let mut line = String::new();
+            if input.read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            self.push_line(line.trim_end_matches(['\n', '\r']));
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    const ECHO: &str = "3,100,4,100,1105,1,0";
+
+    #[test]
+    fn should_push_line() {
+        let mut computer = IntcodeComputer::new(&parse(ECHO));
+        computer.push_line("Hi!");
+        computer.run();
+
+        assert_eq!(computer.take_outputs(), vec![72, 105, 33, 10]);
+    }
+
+    #[test]
+    fn should_separate_non_ascii_values() {
+        let mut computer = IntcodeComputer::new(&parse("104,72,104,105,104,10,104,1000000,99"));
+        computer.run();
+
+        assert_eq!(
+            computer.take_ascii_output(),
+            AsciiOutput {
+                text: String::from("Hi\n"),
+                values: vec![1000000],
+            }
+        );
+    }
+
+    #[test]
+    fn should_run_interactive_session() {
+        let mut computer = IntcodeComputer::new(&parse(ECHO));
+        let mut output: Vec<u8> = vec![];
+
+        let values = computer.run_interactive("first\nsecond\r\n".as_bytes(), &mut output);
+
+        assert_eq!(String::from_utf8(output).unwrap(), "first\nsecond\n");
+        assert!(values.is_empty());
+    }
+}
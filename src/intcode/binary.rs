@@ -0,0 +1,176 @@
+//! Compact binary format for Intcode programs.
+//!
+//! A file starts with the magic bytes `ICB` and a version byte, followed by the
+//! number of cells and the cells themselves as zigzag encoded LEB128 varints,
+//! and ends with the FNV-1a checksum of everything before it (little endian).
+
+use std::{fs, io, path::Path};
+
+use super::load as load_text;
+
+const MAGIC: &[u8; 3] = b"ICB";
+const VERSION: u8 = 1;
+
+pub fn encode(program: &[i64]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+
+    write_varint(&mut bytes, program.len() as u64);
+    for value in program {
+        write_varint(&mut bytes, zigzag(*value));
+    }
+
+    let checksum = fnv1a(&bytes);
+    bytes.extend(checksum.to_le_bytes());
+
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<i64>, String> {
+    if bytes.len() < MAGIC.len() + 1 + 4 {
+        return Err(format!("File has only {} bytes", bytes.len()));
+    }
+    if &bytes[..MAGIC.len()] != MAGIC {
+        return Err(String::from("File is not a binary Intcode program"));
+    }
+    if bytes[MAGIC.len()] != VERSION {
+        return Err(format!("Version {} is not supported", bytes[MAGIC.len()]));
+    }
+
+    let (body, checksum) = bytes.split_at(bytes.len() - 4);
+    let expected = u32::from_le_bytes(checksum.try_into().unwrap());
+    if fnv1a(body) != expected {
+        return Err(String::from("Checksum does not match"));
+    }
+
+    let mut offset = MAGIC.len() + 1;
+    let count = read_varint(body, &mut offset)?;
+
+    let program = (0..count)
+        .map(|_| read_varint(body, &mut offset).map(unzigzag))
+        .collect::<Result<Vec<i64>, String>>()?;
+
+    if offset != body.len() {
+        return Err(format!(
+            "{} bytes left after the last cell",
+            body.len() - offset
+        ));
+    }
+
+    Ok(program)
+}
+
+pub fn save(path: impl AsRef<Path>, program: &[i64]) -> io::Result<()> {
+    fs::write(path, encode(program))
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<i64>> {
+    decode(&fs::read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Converts a program from the comma separated text format into a binary file.
+pub fn convert(text_path: impl AsRef<Path>, binary_path: impl AsRef<Path>) -> io::Result<()> {
+    let program = load_text(&fs::read_to_string(text_path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    save(binary_path, &program)
+}
+
+/// Maps small negative numbers to small unsigned ones: 0, -1, 1, -2, ...
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<u64, String> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*offset)
+            .ok_or_else(|| format!("Varint at byte {} is cut off", offset))?;
+        *offset += 1;
+
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(format!("Varint before byte {} is too long", offset))
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+    #[test]
+    fn should_round_trip() {
+        let program = vec![0, 1, -1, 63, -64, 64, 1125899906842624, i64::MIN, i64::MAX];
+
+        assert_eq!(decode(&encode(&program)), Ok(program));
+        assert_eq!(decode(&encode(&[])), Ok(vec![]));
+    }
+
+    #[test]
+    fn should_be_smaller_than_text() {
+        let bytes = encode(&parse(QUINE));
+
+        assert_eq!(bytes.len(), 4 + 1 + 27 + 4);
+        assert!(bytes.len() < QUINE.len());
+    }
+
+    #[test]
+    fn should_reject_corrupted_files() {
+        let mut bytes = encode(&parse(QUINE));
+
+        assert_eq!(
+            decode(&bytes[..3]),
+            Err(String::from("File has only 3 bytes"))
+        );
+
+        bytes[10] ^= 1;
+        assert_eq!(decode(&bytes), Err(String::from("Checksum does not match")));
+
+        bytes[0] = b'X';
+        assert_eq!(
+            decode(&bytes),
+            Err(String::from("File is not a binary Intcode program"))
+        );
+    }
+
+    #[test]
+    fn should_convert_text_file() {
+        let directory = std::env::temp_dir().join(format!("intcode-binary-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let text = directory.join("quine.txt");
+        let binary = directory.join("quine.icb");
+
+        fs::write(&text, format!("{}\n", QUINE)).unwrap();
+        convert(&text, &binary).unwrap();
+
+        assert_eq!(load(&binary).unwrap(), parse(QUINE));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
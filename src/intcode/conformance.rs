@@ -0,0 +1,310 @@
+//! Every example program published with the Intcode puzzles, run against the
+//! shared VM. Memory the example does not state is what the VM leaves behind,
+//! checked by hand.
+
+use super::{parse, IntcodeComputer, State};
+
+struct Case {
+    name: &'static str,
+    program: &'static str,
+    inputs: &'static [i64],
+    outputs: &'static [i64],
+    memory: &'static str,
+}
+
+const DAY2: [Case; 5] = [
+    Case {
+        name: "day2 add",
+        program: "1,0,0,0,99",
+        inputs: &[],
+        outputs: &[],
+        memory: "2,0,0,0,99",
+    },
+    Case {
+        name: "day2 multiply",
+        program: "2,3,0,3,99",
+        inputs: &[],
+        outputs: &[],
+        memory: "2,3,0,6,99",
+    },
+    Case {
+        name: "day2 multiply past halt",
+        program: "2,4,4,5,99,0",
+        inputs: &[],
+        outputs: &[],
+        memory: "2,4,4,5,99,9801",
+    },
+    Case {
+        name: "day2 overwrite halt",
+        program: "1,1,1,4,99,5,6,0,99",
+        inputs: &[],
+        outputs: &[],
+        memory: "30,1,1,4,2,5,6,0,99",
+    },
+    Case {
+        name: "day2 walkthrough",
+        program: "1,9,10,3,2,3,11,0,99,30,40,50",
+        inputs: &[],
+        outputs: &[],
+        memory: "3500,9,10,70,2,3,11,0,99,30,40,50",
+    },
+];
+
+const LARGER_THAN_8: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99";
+
+const DAY5: [Case; 20] = [
+    Case {
+        name: "day5 echo",
+        program: "3,0,4,0,99",
+        inputs: &[42],
+        outputs: &[42],
+        memory: "42,0,4,0,99",
+    },
+    Case {
+        name: "day5 immediate multiply",
+        program: "1002,4,3,4,33",
+        inputs: &[],
+        outputs: &[],
+        memory: "1002,4,3,4,99",
+    },
+    Case {
+        name: "day5 negative immediate",
+        program: "1101,100,-1,4,0",
+        inputs: &[],
+        outputs: &[],
+        memory: "1101,100,-1,4,99",
+    },
+    Case {
+        name: "day5 equal to 8, position mode",
+        program: "3,9,8,9,10,9,4,9,99,-1,8",
+        inputs: &[8],
+        outputs: &[1],
+        memory: "3,9,8,9,10,9,4,9,99,1,8",
+    },
+    Case {
+        name: "day5 not equal to 8, position mode",
+        program: "3,9,8,9,10,9,4,9,99,-1,8",
+        inputs: &[7],
+        outputs: &[0],
+        memory: "3,9,8,9,10,9,4,9,99,0,8",
+    },
+    Case {
+        name: "day5 less than 8, position mode",
+        program: "3,9,7,9,10,9,4,9,99,-1,8",
+        inputs: &[5],
+        outputs: &[1],
+        memory: "3,9,7,9,10,9,4,9,99,1,8",
+    },
+    Case {
+        name: "day5 not less than 8, position mode",
+        program: "3,9,7,9,10,9,4,9,99,-1,8",
+        inputs: &[8],
+        outputs: &[0],
+        memory: "3,9,7,9,10,9,4,9,99,0,8",
+    },
+    Case {
+        name: "day5 equal to 8, immediate mode",
+        program: "3,3,1108,-1,8,3,4,3,99",
+        inputs: &[8],
+        outputs: &[1],
+        memory: "3,3,1108,1,8,3,4,3,99",
+    },
+    Case {
+        name: "day5 not equal to 8, immediate mode",
+        program: "3,3,1108,-1,8,3,4,3,99",
+        inputs: &[9],
+        outputs: &[0],
+        memory: "3,3,1108,0,8,3,4,3,99",
+    },
+    Case {
+        name: "day5 less than 8, immediate mode",
+        program: "3,3,1107,-1,8,3,4,3,99",
+        inputs: &[-3],
+        outputs: &[1],
+        memory: "3,3,1107,1,8,3,4,3,99",
+    },
+    Case {
+        name: "day5 not less than 8, immediate mode",
+        program: "3,3,1107,-1,8,3,4,3,99",
+        inputs: &[11],
+        outputs: &[0],
+        memory: "3,3,1107,0,8,3,4,3,99",
+    },
+    Case {
+        name: "day5 jump on zero, position mode",
+        program: "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+        inputs: &[0],
+        outputs: &[0],
+        memory: "3,12,6,12,15,1,13,14,13,4,13,99,0,0,1,9",
+    },
+    Case {
+        name: "day5 no jump on non-zero, position mode",
+        program: "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+        inputs: &[5],
+        outputs: &[1],
+        memory: "3,12,6,12,15,1,13,14,13,4,13,99,5,1,1,9",
+    },
+    Case {
+        name: "day5 jump on zero, immediate mode",
+        program: "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+        inputs: &[0],
+        outputs: &[0],
+        memory: "3,3,1105,0,9,1101,0,0,12,4,12,99,0",
+    },
+    Case {
+        name: "day5 no jump on non-zero, immediate mode",
+        program: "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+        inputs: &[-2],
+        outputs: &[1],
+        memory: "3,3,1105,-2,9,1101,0,0,12,4,12,99,1",
+    },
+    Case {
+        name: "day5 below 8",
+        program: LARGER_THAN_8,
+        inputs: &[7],
+        outputs: &[999],
+        memory: "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,7,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99",
+    },
+    Case {
+        name: "day5 equal to 8",
+        program: LARGER_THAN_8,
+        inputs: &[8],
+        outputs: &[1000],
+        memory: "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,1000,8,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99",
+    },
+    Case {
+        name: "day5 above 8",
+        program: LARGER_THAN_8,
+        inputs: &[9],
+        outputs: &[1001],
+        memory: "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,1001,9,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99",
+    },
+    Case {
+        name: "day5 far below 8",
+        program: LARGER_THAN_8,
+        inputs: &[-100],
+        outputs: &[999],
+        memory: "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,-100,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99",
+    },
+    Case {
+        name: "day5 far above 8",
+        program: LARGER_THAN_8,
+        inputs: &[100],
+        outputs: &[1001],
+        memory: "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,1001,100,\
+1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99",
+    },
+];
+
+const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+const DAY9: [Case; 3] = [
+    Case {
+        name: "day9 quine",
+        program: QUINE,
+        inputs: &[],
+        outputs: &[
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ],
+        // the counter at 100 and the comparison at 101 grow the memory
+        memory: "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99,\
+0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,\
+0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,\
+16,1",
+    },
+    Case {
+        name: "day9 sixteen digit product",
+        program: "1102,34915192,34915192,7,4,7,99,0",
+        inputs: &[],
+        outputs: &[1219070632396864],
+        memory: "1102,34915192,34915192,7,4,7,99,1219070632396864",
+    },
+    Case {
+        name: "day9 large number",
+        program: "104,1125899906842624,99",
+        inputs: &[],
+        outputs: &[1125899906842624],
+        memory: "104,1125899906842624,99",
+    },
+];
+
+/// Runs a case and describes how it went wrong, if it did.
+fn check(case: &Case) -> Option<String> {
+    let mut computer = IntcodeComputer::new(&parse(case.program));
+    case.inputs.iter().for_each(|i| computer.push_input(*i));
+
+    let state = computer.run();
+    if state != State::Halted {
+        return Some(format!("{}: stopped in {:?}", case.name, state));
+    }
+
+    let outputs = computer.take_outputs();
+    if outputs != case.outputs {
+        return Some(format!(
+            "{}: expected outputs {:?}, got {:?}",
+            case.name, case.outputs, outputs
+        ));
+    }
+
+    let memory = parse(case.memory);
+    if memory != computer.memory() {
+        return Some(format!(
+            "{}: expected memory {:?}, got {:?}",
+            case.name,
+            memory,
+            computer.memory()
+        ));
+    }
+
+    None
+}
+
+fn check_all(cases: &[Case]) {
+    let failures: Vec<String> = cases.iter().filter_map(check).collect();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn should_run_day2_examples() {
+    check_all(&DAY2);
+}
+
+#[test]
+fn should_run_day5_examples() {
+    check_all(&DAY5);
+}
+
+#[test]
+fn should_run_day9_examples() {
+    check_all(&DAY9);
+}
+
+#[test]
+fn should_report_failing_case() {
+    let case = Case {
+        name: "wrong",
+        program: "1,0,0,0,99",
+        inputs: &[],
+        outputs: &[],
+        memory: "1,0,0,0,99",
+    };
+
+    assert_eq!(
+        check(&case),
+        Some(String::from(
+            "wrong: expected memory [1, 0, 0, 0, 99], got [2, 0, 0, 0, 99]"
+        ))
+    );
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+
+use super::{disassembly::Instruction, observer::Observer};
+
+/// Which instructions of a program ran and which way its conditional
+/// instructions went, collected over any number of runs by attaching it to
+/// every computer running the program.
+pub struct Coverage {
+    program: Vec<i64>,
+    hits: BTreeMap<usize, usize>,
+    /// Times the condition of a 5/6/7/8 instruction was false and true.
+    branches: BTreeMap<usize, [usize; 2]>,
+    /// Conditional instruction whose outcome is not known yet.
+    pending: Option<(usize, i64)>,
+}
+
+impl Coverage {
+    pub fn new(program: &[i64]) -> Coverage {
+        Coverage {
+            program: program.to_vec(),
+            hits: BTreeMap::new(),
+            branches: BTreeMap::new(),
+            pending: None,
+        }
+    }
+
+    fn branch(&mut self, address: usize, condition: bool) {
+        self.branches.entry(address).or_insert([0, 0])[usize::from(condition)] += 1;
+        self.pending = None;
+    }
+
+    pub fn executed(&self) -> Vec<usize> {
+        self.hits.keys().copied().collect()
+    }
+
+    /// Conditional instructions that ran and the condition they never had.
+    pub fn missing_branches(&self) -> Vec<(usize, bool)> {
+        self.branches
+            .iter()
+            .flat_map(|(address, counts)| {
+                [false, true]
+                    .into_iter()
+                    .filter(|c| counts[usize::from(*c)] == 0)
+                    .map(|c| (*address, c))
+            })
+            .collect()
+    }
+
+    /// Annotated disassembly in the style of gcov: every line starts with the
+    /// number of times the instruction ran, `#####` if it never did and `-`
+    /// for data.
+    pub fn report(&self) -> String {
+        let lines = self.listing();
+
+        let instructions = lines.iter().filter(|(_, i)| i.is_some()).count();
+        let directions = 2 * lines
+            .iter()
+            .filter(|(_, i)| i.as_ref().is_some_and(|i| (5..=8).contains(&i.op_code)))
+            .count();
+        let taken = 2 * self.branches.len() - self.missing_branches().len();
+
+        let mut report = format!(
+            "executed {} of {} instructions, {} of {} branch directions\n",
+            self.executed().len(),
+            instructions,
+            taken,
+            directions
+        );
+
+        for (address, instruction) in lines {
+            let Some(instruction) = instruction else {
+                report += &format!(
+                    "{:>5} {:>5}: data {}\n",
+                    "-", address, self.program[address]
+                );
+                continue;
+            };
+
+            let count = match self.hits.get(&address) {
+                Some(hits) => hits.to_string(),
+                None => String::from("#####"),
+            };
+            let mut line = format!("{:>5} {:>5}: {}", count, address, instruction);
+
+            if let Some([no, yes]) = self.branches.get(&address) {
+                let (yes_name, no_name) = match instruction.op_code {
+                    5 | 6 => ("jumped", "fell through"),
+                    _ => ("true", "false"),
+                };
+                line = format!("{:<40} {} {}, {} {}", line, yes_name, yes, no_name, no);
+            }
+
+            report += &line;
+            report.push('\n');
+        }
+
+        report
+    }
+
+    /// Decodes executed addresses first so data the program skips over does not
+    /// throw off the alignment of the code after it.
+    fn listing(&self) -> Vec<(usize, Option<Instruction>)> {
+        let mut lines = vec![];
+        let mut address = 0;
+
+        while address < self.program.len() {
+            let instruction = Instruction::decode(&self.program, address).filter(|i| {
+                self.hits.contains_key(&address)
+                    || (address + 1..address + i.len()).all(|a| !self.hits.contains_key(&a))
+            });
+            let length = instruction.as_ref().map_or(1, |i| i.len());
+
+            lines.push((address, instruction));
+            address += length;
+        }
+
+        lines
+    }
+}
+
+/// Jumps report their taken direction, a jump not taken only shows by the next
+/// fetch or by stopping for input, and comparisons show by the value they write.
+impl Observer for Coverage {
+    fn fetch(&mut self, address: usize, instruction: i64) {
+        if let Some((jump, _)) = self.pending {
+            self.branch(jump, false);
+        }
+
+        *self.hits.entry(address).or_insert(0) += 1;
+        if (5..=8).contains(&(instruction % 100)) {
+            self.pending = Some((address, instruction % 100));
+        }
+    }
+
+    fn write(&mut self, _: usize, value: i64) {
+        if let Some((address, 7 | 8)) = self.pending {
+            self.branch(address, value != 0);
+        }
+    }
+
+    fn jump(&mut self, from: usize, _: usize) {
+        self.branch(from, true);
+    }
+
+    fn wait(&mut self, _: usize) {
+        if let Some((jump, 5 | 6)) = self.pending {
+            self.branch(jump, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::intcode::{parse, IntcodeComputer};
+
+    const LARGER_THAN_8: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99";
+
+    fn cover(program: &str, runs: &[i64]) -> Coverage {
+        let program = parse(program);
+        let coverage = Arc::new(Mutex::new(Coverage::new(&program)));
+
+        for input in runs {
+            let mut computer = IntcodeComputer::new(&program);
+            computer.attach(coverage.clone());
+            computer.push_input(*input);
+            computer.run();
+        }
+
+        Arc::into_inner(coverage).unwrap().into_inner().unwrap()
+    }
+
+    #[test]
+    fn should_record_executed_addresses() {
+        let coverage = cover(LARGER_THAN_8, &[7]);
+
+        assert_eq!(coverage.executed(), vec![0, 2, 6, 9, 13, 31, 33, 46]);
+        assert_eq!(
+            coverage.missing_branches(),
+            vec![(2, true), (6, true), (9, true), (13, false), (33, false)]
+        );
+    }
+
+    #[test]
+    fn should_cover_every_path() {
+        let coverage = cover(LARGER_THAN_8, &[7, 8, 9]);
+
+        // only the unconditional jumps keep a direction that can never happen
+        assert_eq!(
+            coverage.missing_branches(),
+            vec![(16, false), (28, false), (33, false), (42, false)]
+        );
+        assert!(!coverage.report().contains("#####"));
+    }
+
+    #[test]
+    fn should_record_fall_through_before_waiting_for_input() {
+        let program = parse("1105,0,5,3,7,99,0,0");
+        let coverage = Arc::new(Mutex::new(Coverage::new(&program)));
+
+        let mut computer = IntcodeComputer::new(&program);
+        computer.attach(coverage.clone());
+        computer.run();
+
+        assert_eq!(coverage.lock().unwrap().missing_branches(), vec![(0, true)]);
+    }
+
+    #[test]
+    fn should_annotate_disassembly() {
+        let report = cover("3,10,1005,10,7,104,0,4,10,99,0", &[5]).report();
+
+        assert_eq!(
+            report,
+            "executed 4 of 5 instructions, 1 of 2 branch directions\n\
+             \x20   1     0: in [10]\n\
+             \x20   1     2: jnz [10], 7                 jumped 1, fell through 0\n\
+             #####     5: out 0\n\
+             \x20   1     7: out [10]\n\
+             \x20   1     9: hlt\n\
+             \x20   -    10: data 0\n"
+        );
+    }
+}
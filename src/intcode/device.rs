@@ -0,0 +1,134 @@
+use std::{
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use super::IntcodeComputer;
+
+/// Rust code standing in for a range of Intcode memory. Addresses are passed
+/// relative to the start of the mapped range.
+pub trait Device: Send {
+    fn read(&mut self, offset: usize) -> i64;
+
+    fn write(&mut self, offset: usize, value: i64);
+}
+
+/// Cloned computers share their devices.
+#[derive(Clone)]
+pub(super) struct Mapping {
+    range: Range<usize>,
+    device: Arc<Mutex<dyn Device>>,
+}
+
+impl IntcodeComputer {
+    /// Sends every read and write in `range` to `device` instead of memory,
+    /// keep a clone of `device` to look at it while or after the program runs.
+    pub fn map_device<D: Device + 'static>(&mut self, range: Range<usize>, device: Arc<Mutex<D>>) {
+        if let Some(mapping) = self
+            .devices
+            .iter()
+            .find(|m| m.range.start < range.end && range.start < m.range.end)
+        {
+            panic!(
+                "Range {:?} overlaps the device mapped at {:?}!",
+                range, mapping.range
+            );
+        }
+
+        self.devices.push(Mapping { range, device });
+    }
+
+    pub(super) fn read_device(&self, address: usize) -> Option<i64> {
+        let mapping = self.devices.iter().find(|m| m.range.contains(&address))?;
+
+        Some(
+            mapping
+                .device
+                .lock()
+                .unwrap()
+                .read(address - mapping.range.start),
+        )
+    }
+
+    /// Returns `false` if no device is mapped at `address`.
+    pub(super) fn write_device(&self, address: usize, value: i64) -> bool {
+        match self.devices.iter().find(|m| m.range.contains(&address)) {
+            Some(mapping) => {
+                let offset = address - mapping.range.start;
+                mapping.device.lock().unwrap().write(offset, value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    /// Counts up on every read.
+    struct Clock(i64);
+
+    impl Device for Clock {
+        fn read(&mut self, _: usize) -> i64 {
+            self.0 += 1;
+            self.0
+        }
+
+        fn write(&mut self, _: usize, value: i64) {
+            self.0 = value;
+        }
+    }
+
+    #[derive(Default)]
+    struct FrameBuffer {
+        pixels: [i64; 4],
+    }
+
+    impl Device for FrameBuffer {
+        fn read(&mut self, offset: usize) -> i64 {
+            self.pixels[offset]
+        }
+
+        fn write(&mut self, offset: usize, value: i64) {
+            self.pixels[offset] = value;
+        }
+    }
+
+    #[test]
+    fn should_read_from_device() {
+        // sets the clock to 10 and outputs it twice
+        let mut computer = IntcodeComputer::new(&parse("1101,0,10,1000,4,1000,4,1000,99"));
+        let clock = Arc::new(Mutex::new(Clock(0)));
+        computer.map_device(1000..1001, clock.clone());
+        computer.run();
+
+        assert_eq!(computer.take_outputs(), vec![11, 12]);
+        assert_eq!(clock.lock().unwrap().0, 12);
+        assert_eq!(computer.memory().len(), 9);
+    }
+
+    #[test]
+    fn should_write_to_frame_buffer() {
+        // copies the inputs into pixels 1 and 3, the last one through relative mode
+        let mut computer = IntcodeComputer::new(&parse("3,101,109,100,203,3,4,101,99"));
+        let screen = Arc::new(Mutex::new(FrameBuffer::default()));
+        computer.map_device(100..104, screen.clone());
+        computer.push_input(7);
+        computer.push_input(9);
+        computer.run();
+
+        assert_eq!(screen.lock().unwrap().pixels, [0, 7, 0, 9]);
+        assert_eq!(computer.take_outputs(), vec![7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Range 3..5 overlaps the device mapped at 0..4!")]
+    fn should_reject_overlapping_devices() {
+        let mut computer = IntcodeComputer::new(&[99]);
+        computer.map_device(0..4, Arc::new(Mutex::new(FrameBuffer::default())));
+        computer.map_device(3..5, Arc::new(Mutex::new(Clock(0))));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::BTreeMap, fmt};
+
+/// A decoded instruction, parameters keep their mode and raw value.
+#[derive(Debug, PartialEq)]
+pub struct Instruction {
+    pub address: usize,
+    pub op_code: i64,
+    pub params: Vec<(i64, i64)>,
+}
+
+impl Instruction {
+    /// Decodes the instruction at `address`, or `None` if the value there is
+    /// not a valid instruction.
+    pub fn decode(memory: &[i64], address: usize) -> Option<Instruction> {
+        let raw = *memory.get(address)?;
+        let op_code = raw % 100;
+        let count = param_count(op_code)?;
+
+        let params = (1..=count)
+            .map(|offset| {
+                let mode = raw / 10_i64.pow(offset as u32 + 1) % 10;
+                let value = *memory.get(address + offset)?;
+                (0..=2).contains(&mode).then_some((mode, value))
+            })
+            .collect::<Option<Vec<(i64, i64)>>>()?;
+
+        // modes beyond the last parameter make no sense either
+        if raw < 0 || raw / 10_i64.pow(count as u32 + 2) != 0 {
+            return None;
+        }
+        // and neither does writing to an immediate value
+        if target_param(op_code).is_some_and(|n| params[n].0 == 1) {
+            return None;
+        }
+
+        Some(Instruction {
+            address,
+            op_code,
+            params,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        1 + self.params.len()
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self.op_code {
+            1 => "add",
+            2 => "mul",
+            3 => "in",
+            4 => "out",
+            5 => "jnz",
+            6 => "jz",
+            7 => "lt",
+            8 => "eq",
+            9 => "arb",
+            _ => "hlt",
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+
+        for (n, (mode, value)) in self.params.iter().enumerate() {
+            let separator = if n == 0 { " " } else { ", " };
+            match mode {
+                0 => write!(f, "{}[{}]", separator, value)?,
+                1 => write!(f, "{}{}", separator, value)?,
+                _ => write!(f, "{}[rb{:+}]", separator, value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn param_count(op_code: i64) -> Option<usize> {
+    match op_code {
+        1 | 2 | 7 | 8 => Some(3),
+        5 | 6 => Some(2),
+        3 | 4 | 9 => Some(1),
+        99 => Some(0),
+        _ => None,
+    }
+}
+
+fn target_param(op_code: i64) -> Option<usize> {
+    match op_code {
+        1 | 2 | 7 | 8 => Some(2),
+        3 => Some(0),
+        _ => None,
+    }
+}
+
+/// Decodes the instructions reachable from the start through fall through and
+/// jumps to immediate addresses.
+pub fn reachable(program: &[i64]) -> BTreeMap<usize, Instruction> {
+    let mut instructions = BTreeMap::new();
+    let mut pending = vec![0];
+
+    while let Some(address) = pending.pop() {
+        if instructions.contains_key(&address) {
+            continue;
+        }
+        let Some(instruction) = Instruction::decode(program, address) else {
+            continue;
+        };
+
+        pending.extend(successors(&instruction));
+        instructions.insert(address, instruction);
+    }
+
+    instructions
+}
+
+/// Addresses execution can continue at after `instruction`, as far as they are
+/// known before running.
+pub fn successors(instruction: &Instruction) -> Vec<usize> {
+    let mut next = vec![];
+
+    if instruction.op_code != 99 {
+        next.push(instruction.address + instruction.len());
+    }
+    if let 5 | 6 = instruction.op_code {
+        if let (1, target) = instruction.params[1] {
+            next.extend(usize::try_from(target));
+        }
+    }
+
+    next
+}
+
+/// Disassembles `memory` front to back, anything that does not decode is
+/// listed as data.
+pub fn disassemble(memory: &[i64]) -> Vec<(usize, String)> {
+    let mut lines = vec![];
+    let mut address = 0;
+
+    while address < memory.len() {
+        match Instruction::decode(memory, address) {
+            Some(instruction) => {
+                lines.push((address, instruction.to_string()));
+                address += instruction.len();
+            }
+            None => {
+                lines.push((address, format!("data {}", memory[address])));
+                address += 1;
+            }
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    #[test]
+    fn should_decode_modes() {
+        let instruction = Instruction::decode(&parse("21101,-1,8,3"), 0).unwrap();
+
+        assert_eq!(instruction.params, vec![(1, -1), (1, 8), (2, 3)]);
+        assert_eq!(instruction.to_string(), "add -1, 8, [rb+3]");
+    }
+
+    #[test]
+    fn should_reject_invalid_instructions() {
+        assert_eq!(Instruction::decode(&[0], 0), None);
+        assert_eq!(Instruction::decode(&[301], 0), None);
+        assert_eq!(Instruction::decode(&[10099], 0), None);
+        assert_eq!(Instruction::decode(&[-1], 0), None);
+    }
+
+    #[test]
+    fn should_reject_immediate_writes() {
+        assert_eq!(Instruction::decode(&parse("11101,1,1,9"), 0), None);
+        assert_eq!(Instruction::decode(&parse("103,9"), 0), None);
+        assert!(Instruction::decode(&parse("21101,1,1,9"), 0).is_some());
+    }
+
+    #[test]
+    fn should_disassemble_program() {
+        assert_eq!(
+            disassemble(&parse("3,9,8,9,10,9,4,9,99,-1,8")),
+            vec![
+                (0, String::from("in [9]")),
+                (2, String::from("eq [9], [10], [9]")),
+                (6, String::from("out [9]")),
+                (8, String::from("hlt")),
+                (9, String::from("data -1")),
+                (10, String::from("data 8")),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_decode_reachable_code() {
+        // the data after the halt would decode as an add
+        let program = parse("1105,1,5,104,1,104,2,99,1,0,0,0");
+
+        assert_eq!(
+            reachable(&program).keys().collect::<Vec<_>>(),
+            vec![&0, &3, &5, &7]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::disassembly::{reachable, Instruction};
+
+/// Contiguous cells that differ between two memory images.
+#[derive(Debug, PartialEq)]
+pub struct Region {
+    pub start: usize,
+    pub old: Vec<i64>,
+    pub new: Vec<i64>,
+}
+
+impl Region {
+    fn end(&self) -> usize {
+        self.start + self.old.len()
+    }
+}
+
+/// Compares two images cell by cell, cells past the end of the shorter one
+/// count as 0 like they do for the VM.
+pub fn diff(old: &[i64], new: &[i64]) -> Vec<Region> {
+    let cell = |memory: &[i64], address: usize| *memory.get(address).unwrap_or(&0);
+    let mut regions: Vec<Region> = vec![];
+
+    for address in 0..old.len().max(new.len()) {
+        let (before, after) = (cell(old, address), cell(new, address));
+        if before == after {
+            continue;
+        }
+
+        match regions.last_mut() {
+            Some(region) if region.end() == address => {
+                region.old.push(before);
+                region.new.push(after);
+            }
+            _ => regions.push(Region {
+                start: address,
+                old: vec![before],
+                new: vec![after],
+            }),
+        }
+    }
+
+    regions
+}
+
+/// Lists every changed region with its old and new values, followed by the
+/// instructions of `old` it overwrote and what they decode to now.
+pub fn report(old: &[i64], new: &[i64]) -> String {
+    let code = reachable(old);
+    let mut report = String::new();
+
+    for region in diff(old, new) {
+        report += &format!("{}..{}\n", region.start, region.end());
+
+        for (n, (before, after)) in region.old.iter().zip(&region.new).enumerate() {
+            report += &format!("  {:>5}: {} -> {}\n", region.start + n, before, after);
+        }
+
+        for instruction in code
+            .values()
+            .filter(|i| i.address < region.end() && region.start < i.address + i.len())
+        {
+            let now = match Instruction::decode(new, instruction.address) {
+                Some(decoded) => decoded.to_string(),
+                None => format!("data {}", new.get(instruction.address).unwrap_or(&0)),
+            };
+            report += &format!(
+                "  code {}: {} => {}\n",
+                instruction.address, instruction, now
+            );
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    #[test]
+    fn should_group_contiguous_changes() {
+        assert_eq!(
+            diff(&[1, 2, 3, 4, 5], &[1, 7, 8, 4, 5, 0, 9]),
+            vec![
+                Region {
+                    start: 1,
+                    old: vec![2, 3],
+                    new: vec![7, 8],
+                },
+                Region {
+                    start: 6,
+                    old: vec![0],
+                    new: vec![9],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_annotate_changed_code() {
+        let old = parse("1,9,10,3,2,3,11,0,99,30,40,50");
+        let new = parse("3500,9,10,70,2,3,11,0,99,30,40,50");
+
+        assert_eq!(
+            report(&old, &new),
+            "0..1\n\
+             \x20     0: 1 -> 3500\n\
+             \x20 code 0: add [9], [10], [3] => data 3500\n\
+             3..4\n\
+             \x20     3: 3 -> 70\n\
+             \x20 code 0: add [9], [10], [3] => data 3500\n"
+        );
+    }
+
+    #[test]
+    fn should_annotate_code_cut_off_by_shorter_image() {
+        assert_eq!(
+            report(&[1, 0, 0, 0, 99], &[1]),
+            "4..5\n\
+             \x20     4: 99 -> 0\n\
+             \x20 code 4: hlt => data 0\n"
+        );
+    }
+
+    #[test]
+    fn should_not_annotate_data() {
+        let old = parse("1101,1,2,5,99,0");
+        let new = parse("1101,1,2,5,99,3");
+
+        assert_eq!(report(&old, &new), "5..6\n      5: 0 -> 3\n");
+    }
+}
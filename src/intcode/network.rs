@@ -0,0 +1,223 @@
+use super::{IntcodeComputer, State};
+
+/// A directed network of Intcode machines that all run the same program.
+///
+/// Networks are described by a small line based config:
+///
+/// ```text
+/// # five amplifiers with a feedback loop
+/// nodes 5
+/// input 0 9 0
+/// input 1 8
+/// edge 0 1
+/// edge 4 0
+/// result 4
+/// ```
+///
+/// `input` queues initial values for a node (repeated lines append), every
+/// output of a node is copied to all nodes it has an `edge` to, and the last
+/// output of the `result` node is the result of the network.
+#[derive(Debug, PartialEq)]
+pub struct Network {
+    nodes: usize,
+    inputs: Vec<Vec<i64>>,
+    edges: Vec<(usize, usize)>,
+    result: usize,
+}
+
+impl Network {
+    pub fn parse(config: &str) -> Network {
+        let mut nodes: Option<usize> = None;
+        let mut inputs: Vec<(usize, Vec<i64>)> = vec![];
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut result: Option<usize> = None;
+
+        for (number, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            let values: Vec<i64> = words
+                .map(|w| {
+                    w.parse().unwrap_or_else(|_| {
+                        panic!("Value {} in line {} is not a number!", w, number + 1)
+                    })
+                })
+                .collect();
+
+            let index = |value: &i64| {
+                usize::try_from(*value).unwrap_or_else(|_| {
+                    panic!(
+                        "Value {} in line {} is negative: {}",
+                        value,
+                        number + 1,
+                        line
+                    )
+                })
+            };
+
+            match (keyword, values.as_slice()) {
+                ("nodes", [count]) => nodes = Some(index(count)),
+                ("input", [node, values @ ..]) => inputs.push((index(node), values.to_vec())),
+                ("edge", [from, to]) => edges.push((index(from), index(to))),
+                ("result", [node]) => result = Some(index(node)),
+                _ => panic!("Line {} could not be parsed: {}", number + 1, line),
+            }
+        }
+
+        let nodes = nodes.expect("Network config is missing a nodes line!");
+        let result = result.expect("Network config is missing a result line!");
+
+        let mut network = Network {
+            nodes,
+            inputs: vec![vec![]; nodes],
+            edges,
+            result,
+        };
+
+        for (node, values) in inputs {
+            network.check_node(node);
+            network.inputs[node].extend(values);
+        }
+        for (from, to) in network.edges.clone() {
+            network.check_node(from);
+            network.check_node(to);
+        }
+        network.check_node(result);
+
+        network
+    }
+
+    fn check_node(&self, node: usize) {
+        if node >= self.nodes {
+            panic!(
+                "Node {} does not exist in a network of {}!",
+                node, self.nodes
+            );
+        }
+    }
+
+    /// Runs every node until all of them halted and returns the last output of
+    /// the result node.
+    pub fn run(&self, program: &[i64]) -> Option<i64> {
+        let mut computers: Vec<IntcodeComputer> = self
+            .inputs
+            .iter()
+            .map(|inputs| {
+                let mut computer = IntcodeComputer::new(program);
+                inputs.iter().for_each(|i| computer.push_input(*i));
+                computer
+            })
+            .collect();
+
+        let mut result = None;
+
+        while computers.iter().any(|c| c.state() != State::Halted) {
+            let mut progressed = false;
+
+            for node in 0..self.nodes {
+                let computer = &mut computers[node];
+
+                if computer.state() == State::Halted
+                    || computer.state() == State::WaitingForInput && !computer.has_pending_input()
+                {
+                    continue;
+                }
+
+                progressed = true;
+                computer.run();
+
+                for output in computer.take_outputs() {
+                    if node == self.result {
+                        result = Some(output);
+                    }
+                    for (_, to) in self.edges.iter().filter(|(from, _)| *from == node) {
+                        computers[*to].push_input(output);
+                    }
+                }
+            }
+
+            if !progressed {
+                panic!("Network is deadlocked, every running node waits for input!");
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    const FEEDBACK_PROGRAM: &str =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+
+    #[test]
+    fn should_parse_config() {
+        let network = Network::parse(
+            "nodes 3 # comment\n\ninput 0 1 2\ninput 0 3\nedge 0 1\nedge 0 2\nresult 2",
+        );
+
+        assert_eq!(
+            network,
+            Network {
+                nodes: 3,
+                inputs: vec![vec![1, 2, 3], vec![], vec![]],
+                edges: vec![(0, 1), (0, 2)],
+                result: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn should_run_chain() {
+        let program = parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+        let network = Network::parse(
+            "nodes 5\ninput 0 4 0\ninput 1 3\ninput 2 2\ninput 3 1\ninput 4 0\n\
+             edge 0 1\nedge 1 2\nedge 2 3\nedge 3 4\nresult 4",
+        );
+
+        assert_eq!(network.run(&program), Some(43210));
+    }
+
+    #[test]
+    fn should_run_feedback_loop() {
+        let program = parse(FEEDBACK_PROGRAM);
+        let network = Network::parse(
+            "nodes 5\ninput 0 9 0\ninput 1 8\ninput 2 7\ninput 3 6\ninput 4 5\n\
+             edge 0 1\nedge 1 2\nedge 2 3\nedge 3 4\nedge 4 0\nresult 4",
+        );
+
+        assert_eq!(network.run(&program), Some(139629729));
+    }
+
+    #[test]
+    fn should_fan_out_and_in() {
+        // every node outputs the sum of its two inputs
+        let program = parse("3,11,3,12,1,11,12,13,4,13,99,0,0,0");
+        let network = Network::parse(
+            "nodes 3\ninput 0 1 2\ninput 1 10\nedge 0 1\nedge 0 2\nedge 1 2\nresult 2",
+        );
+
+        assert_eq!(network.run(&program), Some(16));
+    }
+
+    #[test]
+    #[should_panic(expected = "Value -1 in line 1 is negative: nodes -1")]
+    fn should_reject_negative_values() {
+        Network::parse("nodes -1\nresult 0");
+    }
+
+    #[test]
+    #[should_panic(expected = "deadlocked")]
+    fn should_detect_deadlock() {
+        let program = parse("3,0,99");
+        Network::parse("nodes 2\nedge 0 1\nresult 1").run(&program);
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::{Arc, Mutex};
+
+use super::IntcodeComputer;
+
+/// Callbacks for everything the VM does, implement the ones you need. Reads
+/// are only reported for position and relative mode parameters, the
+/// instruction and immediate values are part of the fetch.
+pub trait Observer: Send {
+    fn fetch(&mut self, _address: usize, _instruction: i64) {}
+
+    fn read(&mut self, _address: usize, _value: i64) {}
+
+    fn write(&mut self, _address: usize, _value: i64) {}
+
+    fn jump(&mut self, _from: usize, _to: usize) {}
+
+    fn input(&mut self, _value: i64) {}
+
+    fn output(&mut self, _value: i64) {}
+
+    /// The program stopped at `address` to wait for input.
+    fn wait(&mut self, _address: usize) {}
+}
+
+/// Cloned computers share their observers.
+pub(super) type Attached = Arc<Mutex<dyn Observer>>;
+
+impl IntcodeComputer {
+    /// Keep a clone of `observer` to look at what it collected.
+    pub fn attach<O: Observer + 'static>(&mut self, observer: Arc<Mutex<O>>) {
+        self.observers.push(observer);
+    }
+
+    /// Calls `notify` for every observer, the check for none keeps the VM as
+    /// fast as without observers.
+    pub(super) fn observe(&self, notify: impl Fn(&mut dyn Observer)) {
+        if self.observers.is_empty() {
+            return;
+        }
+
+        for observer in &self.observers {
+            notify(&mut *observer.lock().unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::{parse, State};
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Observer for Trace {
+        fn fetch(&mut self, address: usize, instruction: i64) {
+            self.0.push(format!("fetch {} {}", address, instruction));
+        }
+
+        fn read(&mut self, address: usize, value: i64) {
+            self.0.push(format!("read {} {}", address, value));
+        }
+
+        fn write(&mut self, address: usize, value: i64) {
+            self.0.push(format!("write {} {}", address, value));
+        }
+
+        fn jump(&mut self, from: usize, to: usize) {
+            self.0.push(format!("jump {} {}", from, to));
+        }
+
+        fn input(&mut self, value: i64) {
+            self.0.push(format!("input {}", value));
+        }
+
+        fn output(&mut self, value: i64) {
+            self.0.push(format!("output {}", value));
+        }
+
+        fn wait(&mut self, address: usize) {
+            self.0.push(format!("wait {}", address));
+        }
+    }
+
+    /// Remembers every value written to a single address.
+    struct Watchpoint {
+        address: usize,
+        values: Vec<i64>,
+    }
+
+    impl Observer for Watchpoint {
+        fn write(&mut self, address: usize, value: i64) {
+            if address == self.address {
+                self.values.push(value);
+            }
+        }
+    }
+
+    #[test]
+    fn should_trace_every_event() {
+        let mut computer = IntcodeComputer::new(&parse("3,10,1005,10,7,99,99,4,10,99,0"));
+        let trace = Arc::new(Mutex::new(Trace::default()));
+        computer.attach(trace.clone());
+
+        assert_eq!(computer.run(), State::WaitingForInput);
+        computer.push_input(4);
+        computer.run();
+
+        assert_eq!(
+            trace.lock().unwrap().0,
+            vec![
+                "wait 0",
+                "fetch 0 3",
+                "input 4",
+                "write 10 4",
+                "fetch 2 1005",
+                "read 10 4",
+                "jump 2 7",
+                "fetch 7 4",
+                "read 10 4",
+                "output 4",
+                "fetch 9 99",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_watch_address() {
+        // counts down from 3 in cell 12
+        let mut computer = IntcodeComputer::new(&parse("1101,0,3,12,1001,12,-1,12,1005,12,4,99"));
+        let watchpoint = Arc::new(Mutex::new(Watchpoint {
+            address: 12,
+            values: vec![],
+        }));
+        computer.attach(watchpoint.clone());
+        computer.run();
+
+        assert_eq!(watchpoint.lock().unwrap().values, vec![3, 2, 1, 0]);
+    }
+}
@@ -0,0 +1,629 @@
+//! Peephole optimizations that keep every instruction at its address and
+//! length, so computed jumps still land where they did.
+//!
+//! The program is first run abstractly from the start, following both ways of
+//! a jump on an unknown value and keeping the relative base as a range. Only
+//! instructions whose cells nothing reads or writes on the way are rewritten,
+//! and nothing is if a write may land in code or a relative access has no lower
+//! bound. A jump to an address that is not known up front is taken to be the
+//! return of a call, which lands just after an unconditional jump like the
+//! calls of compiled programs do.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::disassembly::{reachable, Instruction};
+
+/// Updates of an instruction's state before its ranges are widened to no bound.
+const WIDEN_AFTER: usize = 8;
+
+/// Relative accesses over more cells are taken to reach every cell above.
+const MAX_SPAN: i64 = 64;
+
+/// Folds arithmetic on two immediates into a stored constant, turns jumps on an
+/// immediate condition into direct jumps or no-ops and skips stores that are
+/// overwritten before anything reads them.
+pub fn optimize(program: &[i64]) -> Vec<i64> {
+    let Some(analysis) = analyse(program) else {
+        return program.to_vec();
+    };
+
+    let instructions = &analysis.executed;
+    let mut optimized = program.to_vec();
+
+    // cells of an instruction that also runs as part of another one keep both
+    let mut runs = BTreeMap::new();
+    for i in instructions.values() {
+        for cell in i.address..i.address + i.len() {
+            *runs.entry(cell).or_insert(0) += 1;
+        }
+    }
+
+    let static_code = |i: &Instruction| {
+        (i.address..i.address + i.len())
+            .all(|c| runs[&c] == 1 && !analysis.read.contains(c) && !analysis.written.contains(c))
+    };
+
+    for instruction in instructions.values().filter(|i| static_code(i)) {
+        match fold(instruction) {
+            Some(replacement)
+                if replacement != encode(instruction.op_code, &instruction.params) =>
+            {
+                replace(&mut optimized, instruction.address, &replacement)
+            }
+            _ => {}
+        }
+    }
+
+    for instruction in instructions.values().filter(|i| static_code(i)) {
+        if is_dead_store(instructions, instruction) {
+            // jump over the store, its last cell is never executed
+            let next = instruction.address + instruction.len();
+            replace(
+                &mut optimized,
+                instruction.address,
+                &encode(5, &[(1, 1), (1, next as i64)]),
+            );
+        }
+    }
+
+    optimized
+}
+
+/// Lowest and highest address or relative base, `i64::MIN` and `i64::MAX`
+/// standing for no bound.
+type Range = (i64, i64);
+
+fn shift((lo, hi): Range, offset: i64) -> Range {
+    let bound = |bound: i64| match bound {
+        i64::MIN | i64::MAX => bound,
+        _ => bound.saturating_add(offset),
+    };
+
+    (bound(lo), bound(hi))
+}
+
+/// Cells that may be accessed somewhere in the program.
+#[derive(Default)]
+struct Cells {
+    listed: BTreeSet<usize>,
+    /// Every cell from here on.
+    from: Option<usize>,
+    /// Whether a relative access may reach any cell at all.
+    unbounded: bool,
+}
+
+impl Cells {
+    fn insert(&mut self, (lo, hi): Range) {
+        // the VM fails on negative addresses
+        if hi < 0 {
+            return;
+        }
+        if lo == i64::MIN {
+            self.unbounded = true;
+            return;
+        }
+
+        let lo = lo.max(0);
+        if hi - lo <= MAX_SPAN {
+            self.listed.extend(lo as usize..=hi as usize);
+        } else {
+            self.from = Some(self.from.map_or(lo as usize, |f| f.min(lo as usize)));
+        }
+    }
+
+    fn contains(&self, cell: usize) -> bool {
+        self.listed.contains(&cell) || self.from.is_some_and(|f| cell >= f)
+    }
+}
+
+/// What is known about the machine when it reaches an instruction.
+#[derive(Clone, PartialEq)]
+struct State {
+    relative_base: Range,
+    /// Cells that may differ from the program, `None` if their value is unknown.
+    cells: BTreeMap<usize, Option<i64>>,
+    /// Every cell from here on is unknown.
+    clobbered: usize,
+}
+
+impl State {
+    fn value(&self, program: &[i64], address: usize) -> Option<i64> {
+        if address >= self.clobbered {
+            return None;
+        }
+        match self.cells.get(&address) {
+            Some(value) => *value,
+            None => Some(program.get(address).copied().unwrap_or(0)),
+        }
+    }
+
+    /// Addresses a parameter may refer to, `None` for immediates.
+    fn addresses(&self, (mode, value): (i64, i64)) -> Option<Range> {
+        match mode {
+            0 => Some((value, value)),
+            2 => Some(shift(self.relative_base, value)),
+            _ => None,
+        }
+    }
+
+    fn read(&self, program: &[i64], param: (i64, i64), read: &mut Cells) -> Option<i64> {
+        let Some((lo, hi)) = self.addresses(param) else {
+            return Some(param.1);
+        };
+        read.insert((lo, hi));
+
+        match usize::try_from(lo) {
+            Ok(address) if lo == hi => self.value(program, address),
+            _ => None,
+        }
+    }
+
+    fn write(
+        &mut self,
+        program: &[i64],
+        param: (i64, i64),
+        value: Option<i64>,
+        written: &mut Cells,
+    ) {
+        // decoding rejects writes to immediates
+        let (lo, hi) = self.addresses(param).unwrap();
+        written.insert((lo, hi));
+
+        if hi < 0 || lo == i64::MIN {
+            return;
+        }
+        let lo = lo.max(0);
+        if lo == hi {
+            self.cells.insert(lo as usize, value);
+        } else if hi - lo <= MAX_SPAN {
+            for address in lo as usize..=hi as usize {
+                if self.value(program, address) != value {
+                    self.cells.insert(address, None);
+                }
+            }
+        } else {
+            self.clobbered = self.clobbered.min(lo as usize);
+        }
+    }
+
+    /// The state after a call returns, which may have changed any cell.
+    fn forget(&self) -> State {
+        State {
+            relative_base: self.relative_base,
+            cells: BTreeMap::new(),
+            clobbered: 0,
+        }
+    }
+
+    fn join(&self, other: &State, program: &[i64]) -> State {
+        let clobbered = self.clobbered.min(other.clobbered);
+        let cells = self
+            .cells
+            .keys()
+            .chain(other.cells.keys())
+            .map(|&address| {
+                let value = self.value(program, address);
+                (
+                    address,
+                    value.filter(|_| value == other.value(program, address)),
+                )
+            })
+            .collect();
+
+        State {
+            relative_base: (
+                self.relative_base.0.min(other.relative_base.0),
+                self.relative_base.1.max(other.relative_base.1),
+            ),
+            cells,
+            clobbered,
+        }
+        .normalized(program)
+    }
+
+    /// Drops the bounds that keep moving so loops reach a fixed point.
+    fn widen(&self, joined: State) -> State {
+        State {
+            relative_base: (
+                if joined.relative_base.0 < self.relative_base.0 {
+                    i64::MIN
+                } else {
+                    joined.relative_base.0
+                },
+                if joined.relative_base.1 > self.relative_base.1 {
+                    i64::MAX
+                } else {
+                    joined.relative_base.1
+                },
+            ),
+            clobbered: if joined.clobbered < self.clobbered {
+                0
+            } else {
+                joined.clobbered
+            },
+            cells: joined.cells,
+        }
+    }
+
+    fn normalized(mut self, program: &[i64]) -> State {
+        let clobbered = self.clobbered;
+        self.cells.retain(|address, value| {
+            *address < clobbered && *value != Some(program.get(*address).copied().unwrap_or(0))
+        });
+        self
+    }
+}
+
+/// The result of running a program abstractly.
+struct Analysis {
+    executed: BTreeMap<usize, Instruction>,
+    read: Cells,
+    written: Cells,
+}
+
+/// Runs the program abstractly, or returns `None` if it may write into its
+/// own code or access memory anywhere.
+fn analyse(program: &[i64]) -> Option<Analysis> {
+    let returns = reachable(program)
+        .values()
+        .any(|i| matches!(i.op_code, 5 | 6) && i.params[1].0 != 1);
+
+    let mut analysis = Analysis {
+        executed: BTreeMap::new(),
+        read: Cells::default(),
+        written: Cells::default(),
+    };
+    let start = State {
+        relative_base: (0, 0),
+        cells: BTreeMap::new(),
+        clobbered: usize::MAX,
+    };
+    let mut states = BTreeMap::from([(0, start)]);
+    let mut updates: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut pending = vec![0];
+
+    while let Some(address) = pending.pop() {
+        // the VM fails on anything that does not decode, which it must keep doing
+        let Some(instruction) = Instruction::decode(program, address) else {
+            analysis.read.listed.insert(address);
+            continue;
+        };
+
+        let state = states[&address].clone();
+        for (successor, incoming) in step(program, &instruction, state, &mut analysis, returns) {
+            let count = updates.entry(successor).or_default();
+            let merged = match states.get(&successor) {
+                Some(old) if *count >= WIDEN_AFTER => {
+                    old.widen(old.join(&incoming, program)).normalized(program)
+                }
+                Some(old) => old.join(&incoming, program),
+                None => incoming.normalized(program),
+            };
+
+            if states.get(&successor) != Some(&merged) {
+                *count += 1;
+                states.insert(successor, merged);
+                pending.push(successor);
+            }
+        }
+
+        analysis.executed.insert(address, instruction);
+    }
+
+    // the instructions were decoded from the program, so they must not change
+    let modified = analysis
+        .executed
+        .values()
+        .any(|i| (i.address..i.address + i.len()).any(|c| analysis.written.contains(c)));
+
+    if modified || analysis.read.unbounded || analysis.written.unbounded {
+        None
+    } else {
+        Some(analysis)
+    }
+}
+
+/// Runs one instruction abstractly and returns where execution may continue
+/// with which state.
+fn step(
+    program: &[i64],
+    instruction: &Instruction,
+    mut state: State,
+    analysis: &mut Analysis,
+    returns: bool,
+) -> Vec<(usize, State)> {
+    let params = &instruction.params;
+    let next = instruction.address + instruction.len();
+    let read = &mut analysis.read;
+
+    match instruction.op_code {
+        1 | 2 | 7 | 8 => {
+            let a = state.read(program, params[0], read);
+            let b = state.read(program, params[1], read);
+            let value = a.zip(b).and_then(|(a, b)| match instruction.op_code {
+                // the VM fails on overflows
+                1 => a.checked_add(b),
+                2 => a.checked_mul(b),
+                7 => Some(i64::from(a < b)),
+                _ => Some(i64::from(a == b)),
+            });
+            state.write(program, params[2], value, &mut analysis.written);
+            vec![(next, state)]
+        }
+        3 => {
+            state.write(program, params[0], None, &mut analysis.written);
+            vec![(next, state)]
+        }
+        4 => {
+            state.read(program, params[0], read);
+            vec![(next, state)]
+        }
+        5 | 6 => {
+            let condition = state.read(program, params[0], read);
+            let target = state.read(program, params[1], read);
+            let taken = condition.map(|c| (instruction.op_code == 5) == (c != 0));
+
+            let mut successors = vec![];
+            if taken != Some(false) {
+                if let Some(target) = target.and_then(|t| usize::try_from(t).ok()) {
+                    successors.push((target, state.clone()));
+                }
+            }
+            match taken {
+                Some(true) if returns => successors.push((next, state.forget())),
+                Some(true) => {}
+                _ => successors.push((next, state)),
+            }
+            successors
+        }
+        9 => {
+            state.relative_base = match state.read(program, params[0], read) {
+                Some(offset) => shift(state.relative_base, offset),
+                None => (i64::MIN, i64::MAX),
+            };
+            vec![(next, state)]
+        }
+        _ => vec![],
+    }
+}
+
+fn fold(instruction: &Instruction) -> Option<Vec<i64>> {
+    let params = &instruction.params;
+
+    match instruction.op_code {
+        1 | 2 | 7 | 8 if params[0].0 == 1 && params[1].0 == 1 => {
+            let (a, b) = (params[0].1, params[1].1);
+            let value = match instruction.op_code {
+                // leave overflows to the VM to report
+                1 => a.checked_add(b)?,
+                2 => a.checked_mul(b)?,
+                7 => i64::from(a < b),
+                _ => i64::from(a == b),
+            };
+            Some(encode(1, &[(1, value), (1, 0), params[2]]))
+        }
+        5 | 6 if params[0].0 == 1 => {
+            let taken = (instruction.op_code == 5) == (params[0].1 != 0);
+            if taken {
+                Some(encode(5, &[(1, 1), params[1]]))
+            } else {
+                // a jump that never happens
+                Some(encode(5, &[(1, 0), (1, 0)]))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A store to a fixed address is dead if the instructions straight after it
+/// overwrite the address before reading it, without jumping, halting or
+/// touching memory in relative mode on the way.
+fn is_dead_store(instructions: &BTreeMap<usize, Instruction>, store: &Instruction) -> bool {
+    let target = match (store.op_code, store.params.last()) {
+        (1 | 2 | 7 | 8, Some((0, target))) if *target >= 0 => *target,
+        _ => return false,
+    };
+
+    // skipping a read the VM would fail on hides the failure
+    if store
+        .params
+        .iter()
+        .any(|(mode, value)| *mode == 2 || *mode == 0 && *value < 0)
+    {
+        return false;
+    }
+
+    // a store into code changes what runs before it is overwritten
+    if instructions
+        .values()
+        .any(|i| (i.address..i.address + i.len()).contains(&(target as usize)))
+    {
+        return false;
+    }
+
+    let mut address = store.address + store.len();
+
+    while let Some(instruction) = instructions.get(&address) {
+        if matches!(instruction.op_code, 5 | 6 | 9 | 99)
+            || instruction.params.iter().any(|(mode, _)| *mode == 2)
+        {
+            return false;
+        }
+
+        let params = &instruction.params;
+        let (reads, writes) = match instruction.op_code {
+            3 => (&params[..0], Some(params[0])),
+            4 => (&params[..1], None),
+            _ => (&params[..2], Some(params[2])),
+        };
+
+        if reads.contains(&(0, target)) {
+            return false;
+        }
+        if writes == Some((0, target)) {
+            return true;
+        }
+
+        address += instruction.len();
+    }
+
+    false
+}
+
+fn encode(op_code: i64, params: &[(i64, i64)]) -> Vec<i64> {
+    let modes: i64 = params
+        .iter()
+        .enumerate()
+        .map(|(n, (mode, _))| mode * 10_i64.pow(n as u32 + 2))
+        .sum();
+
+    std::iter::once(op_code + modes)
+        .chain(params.iter().map(|(_, value)| *value))
+        .collect()
+}
+
+fn replace(program: &mut [i64], address: usize, cells: &[i64]) {
+    program[address..address + cells.len()].copy_from_slice(cells);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::intcode::{parse, IntcodeComputer};
+
+    const LARGER_THAN_8: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,\
+4,20,1105,1,46,98,99";
+
+    const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+    fn run(program: &[i64], input: &[i64]) -> Vec<i64> {
+        let mut computer = IntcodeComputer::new(program);
+        input.iter().for_each(|i| computer.push_input(*i));
+        computer.run();
+        computer.take_outputs()
+    }
+
+    fn assert_same_outputs(program: &[i64], inputs: &[i64]) {
+        let optimized = optimize(program);
+
+        for input in inputs {
+            assert_eq!(run(&optimized, &[*input]), run(program, &[*input]));
+        }
+    }
+
+    #[test]
+    fn should_fold_immediate_arithmetic() {
+        assert_eq!(
+            optimize(&parse("1102,6,7,9,1107,1,2,10,99,0,0")),
+            parse("1101,42,0,9,1101,1,0,10,99,0,0")
+        );
+    }
+
+    #[test]
+    fn should_resolve_immediate_jumps() {
+        assert_eq!(
+            optimize(&parse("1106,3,99,1106,0,7,99,104,1,99")),
+            parse("1105,0,0,1105,1,7,99,104,1,99")
+        );
+    }
+
+    #[test]
+    fn should_remove_dead_stores() {
+        // the first store is overwritten before the output reads it
+        assert_eq!(
+            optimize(&parse("1001,11,1,12,1002,11,3,12,4,12,99,5,0")),
+            parse("1105,1,4,12,1002,11,3,12,4,12,99,5,0")
+        );
+        // or the store changes the output instruction on the way
+        let program = parse("1101,5,0,5,104,0,1101,6,0,5,99");
+        assert_eq!(optimize(&program), program);
+        // or the store itself fails
+        let program = parse("1001,-1,1,12,1002,11,3,12,4,12,99,5,0");
+        assert_eq!(optimize(&program), program);
+        // or something reads it in between
+        assert_eq!(
+            optimize(&parse("1001,11,1,12,4,12,1002,11,3,12,99,5,0")),
+            parse("1001,11,1,12,4,12,1002,11,3,12,99,5,0")
+        );
+    }
+
+    #[test]
+    fn should_keep_code_read_as_data() {
+        // outputs the second cell of the addition
+        let program = parse("1101,1,2,8,4,1,99,0,0");
+
+        assert_eq!(optimize(&program), program);
+    }
+
+    #[test]
+    fn should_keep_data_after_jumps() {
+        // the multiplication is never run, only read through the relative base
+        let program = parse("1105,1,7,1102,2,3,0,109,3,204,1,99");
+
+        assert_eq!(optimize(&program), program);
+        assert_eq!(run(&optimize(&program), &[]), vec![2]);
+    }
+
+    #[test]
+    fn should_keep_programs_with_unknown_accesses() {
+        // the relative write may land in the folded addition
+        let program = parse("21101,1,2,1,1101,1,2,9,99,0");
+        assert_eq!(optimize(&program), program);
+        // the relative base moves by an input, so the write may land anywhere
+        let program = parse("3,16,9,16,21101,1,2,0,1101,1,2,15,4,15,99,0,0");
+        assert_eq!(optimize(&program), program);
+    }
+
+    #[test]
+    fn should_optimize_calls() {
+        // the call at 6 returns to 9 through the address stored on the stack
+        let program = parse("109,100,21101,9,0,0,1105,1,12,4,19,99,1102,6,7,19,2106,0,0,0");
+
+        assert_eq!(
+            optimize(&program),
+            parse("109,100,21101,9,0,0,1105,1,12,4,19,99,1101,42,0,19,2105,1,0,0")
+        );
+        assert_same_outputs(&program, &[0]);
+    }
+
+    #[test]
+    fn should_optimize_recursion() {
+        // counts down from 3, the stack grows above 100 with every call
+        let program = parse(
+            "109,100,21101,3,0,1,21101,13,0,0,1105,1,14,99,1206,1,38,204,1,1102,2,3,50,\
+21201,1,-1,3,21101,36,0,2,109,2,1105,1,14,109,-2,2106,0,0",
+        );
+        let mut optimized = program.clone();
+        replace(&mut optimized, 19, &[1101, 6, 0, 50]);
+        replace(&mut optimized, 38, &[2105, 1, 0]);
+
+        assert_eq!(optimize(&program), optimized);
+        assert_eq!(run(&optimized, &[]), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn should_keep_outputs_of_examples() {
+        assert_same_outputs(&parse(LARGER_THAN_8), &[-5, 7, 8, 9, 100]);
+        assert_same_outputs(&parse(QUINE), &[0]);
+        assert_ne!(optimize(&parse(LARGER_THAN_8)), parse(LARGER_THAN_8));
+    }
+
+    #[test]
+    fn should_keep_outputs_of_diagnostic() {
+        let program = parse(&fs::read_to_string("src/day5.txt").unwrap());
+
+        assert_same_outputs(&program, &[1, 5]);
+        assert_ne!(optimize(&program), program);
+    }
+
+    #[test]
+    fn should_keep_outputs_of_boost() {
+        let program = parse(&fs::read_to_string("src/day9.txt").unwrap());
+
+        assert_same_outputs(&program, &[1, 2]);
+        assert_ne!(optimize(&program), program);
+    }
+}
@@ -0,0 +1,260 @@
+//! Recording of the values a program consumes and produces, so interactive runs
+//! can be replayed against a changed interpreter.
+//!
+//! Session files have one event per line: the number of instructions executed
+//! before it, `in` or `out` and the value, like `12 in 5`.
+
+use std::{fmt, fs, io, path::Path};
+
+use super::{IntcodeComputer, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub step: usize,
+    pub direction: Direction,
+    pub value: i64,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::In => "in",
+            Direction::Out => "out",
+        };
+
+        write!(f, "{} {} {}", self.step, direction, self.value)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Session {
+    pub events: Vec<Event>,
+}
+
+impl Session {
+    pub fn parse(text: &str) -> Result<Session, String> {
+        let events = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                parse_event(line)
+                    .ok_or_else(|| format!("Line {} is not an event: {}", number + 1, line))
+            })
+            .collect::<Result<Vec<Event>, String>>()?;
+
+        Ok(Session { events })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Session> {
+        Session::parse(&fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Runs `program` with the recorded inputs and compares every event it
+    /// produces with the recording, stopping at the first one that differs or
+    /// once the next event is overdue, so a program that hangs diverges too.
+    /// Nothing past the last recorded step is checked. Only the shared VM counts
+    /// steps, so the per-day interpreters can't be replayed against a session.
+    pub fn replay(&self, program: &[i64]) -> Result<(), Divergence> {
+        let mut computer = IntcodeComputer::new(program);
+        computer.start_recording();
+        self.events
+            .iter()
+            .filter(|e| e.direction == Direction::In)
+            .for_each(|e| computer.push_input(e.value));
+
+        let mut checked = 0;
+
+        loop {
+            let state = computer.step();
+            let actual = &computer.session.as_ref().unwrap().events;
+
+            while checked < actual.len() {
+                if self.events.get(checked) != Some(&actual[checked]) {
+                    return Err(Divergence {
+                        index: checked,
+                        expected: self.events.get(checked).copied(),
+                        actual: Some(actual[checked]),
+                    });
+                }
+                checked += 1;
+            }
+
+            if state != State::Running {
+                break;
+            }
+
+            match self.events.get(checked) {
+                Some(expected) if computer.steps > expected.step => {
+                    return Err(Divergence {
+                        index: checked,
+                        expected: Some(*expected),
+                        actual: None,
+                    });
+                }
+                None if computer.steps > self.events.last().map_or(0, |e| e.step) => break,
+                _ => {}
+            }
+        }
+
+        match self.events.get(checked) {
+            Some(expected) => Err(Divergence {
+                index: checked,
+                expected: Some(*expected),
+                actual: None,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_event(line: &str) -> Option<Event> {
+    let mut words = line.split_whitespace();
+
+    let step = words.next()?.parse().ok()?;
+    let direction = match words.next()? {
+        "in" => Direction::In,
+        "out" => Direction::Out,
+        _ => return None,
+    };
+    let value = words.next()?.parse().ok()?;
+
+    words.next().is_none().then_some(Event {
+        step,
+        direction,
+        value,
+    })
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.events.iter().try_for_each(|e| writeln!(f, "{}", e))
+    }
+}
+
+/// The first event where a replay differs from its recording, `None` where
+/// one side has no event at all.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub index: usize,
+    pub expected: Option<Event>,
+    pub actual: Option<Event>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |event: Option<Event>| match event {
+            Some(event) => event.to_string(),
+            None => String::from("nothing"),
+        };
+
+        write!(
+            f,
+            "Event {} diverged, expected {} but got {}",
+            self.index,
+            describe(self.expected),
+            describe(self.actual)
+        )
+    }
+}
+
+impl IntcodeComputer {
+    /// Records every input consumed and output produced from now on.
+    pub fn start_recording(&mut self) {
+        self.session = Some(Session::default());
+    }
+
+    pub fn take_session(&mut self) -> Option<Session> {
+        self.session.take()
+    }
+
+    pub(super) fn record(&mut self, direction: Direction, value: i64) {
+        if let Some(session) = &mut self.session {
+            session.events.push(Event {
+                step: self.steps,
+                direction,
+                value,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse;
+
+    /// Outputs the sum of every pair of inputs until it reads a zero.
+    const ADDER: &str = "3,20,1006,20,16,3,21,1,20,21,22,4,22,1105,1,0,99";
+
+    fn record(program: &str, inputs: &[i64]) -> Session {
+        let mut computer = IntcodeComputer::new(&parse(program));
+        computer.start_recording();
+        inputs.iter().for_each(|i| computer.push_input(*i));
+        computer.run();
+
+        computer.take_session().unwrap()
+    }
+
+    #[test]
+    fn should_record_session() {
+        let session = record(ADDER, &[2, 3, 0]);
+
+        assert_eq!(session.to_string(), "0 in 2\n2 in 3\n4 out 5\n6 in 0\n");
+        assert_eq!(Session::parse(&session.to_string()), Ok(session));
+    }
+
+    #[test]
+    fn should_replay_session() {
+        let session = record(ADDER, &[2, 3, 4, 5, 0]);
+
+        assert_eq!(session.replay(&parse(ADDER)), Ok(()));
+    }
+
+    #[test]
+    fn should_stop_at_first_divergence() {
+        let session = record(ADDER, &[2, 3, 4, 5, 0]);
+        // multiplies instead of adding
+        let changed = parse(&ADDER.replacen("1,20,21,22", "2,20,21,22", 1));
+
+        let divergence = session.replay(&changed).unwrap_err();
+
+        assert_eq!(
+            divergence.to_string(),
+            "Event 2 diverged, expected 4 out 5 but got 4 out 6"
+        );
+    }
+
+    #[test]
+    fn should_stop_when_an_event_is_overdue() {
+        let session = record(ADDER, &[2, 3, 4, 5, 0]);
+        // loops forever after the first output
+        let changed = parse(&ADDER.replacen("1105,1,0", "1105,1,13", 1));
+
+        let divergence = session.replay(&changed).unwrap_err();
+
+        assert_eq!(
+            divergence.to_string(),
+            "Event 3 diverged, expected 6 in 4 but got nothing"
+        );
+    }
+
+    #[test]
+    fn should_reject_malformed_lines() {
+        assert_eq!(
+            Session::parse("0 in 2\n2 sideways 3"),
+            Err(String::from("Line 2 is not an event: 2 sideways 3"))
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Turns Intcode programs into Rust source for programs that are run too often
+//! for the interpreter.
+//!
+//! Every instruction reachable from the start becomes an arm of a `match` on
+//! the instruction pointer, so jumps to computed addresses still work as long
+//! as they land on one of those instructions, and hand over to the interpreter
+//! otherwise.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::disassembly::{reachable, successors, Instruction};
+
+/// Generates `pub fn {name}(input: impl IntoIterator<Item = i64>) -> Vec<i64>`
+/// which returns every output, stopping early if it runs out of input like the
+/// interpreter does. Programs that write into their own code at a fixed address
+/// are wrapped around the interpreter instead, and the native code hands over
+/// to it once a relative write lands in code. Either way the source refers to
+/// `crate::intcode`, so it only compiles inside this crate.
+pub fn transpile(program: &[i64], name: &str) -> String {
+    let instructions = reachable(program);
+
+    match self_modification(&instructions) {
+        Some(address) => interpreted(program, name, address),
+        None => native(program, name, &instructions),
+    }
+}
+
+/// Cells of the reachable instructions, and of the addresses they continue at
+/// even if nothing decodes there yet.
+fn code_cells(instructions: &BTreeMap<usize, Instruction>) -> BTreeSet<usize> {
+    instructions
+        .values()
+        .flat_map(|i| (i.address..i.address + i.len()).chain(successors(i)))
+        .collect()
+}
+
+/// Returns the first instruction that writes into code at a fixed address.
+fn self_modification(instructions: &BTreeMap<usize, Instruction>) -> Option<usize> {
+    let code = code_cells(instructions);
+
+    instructions
+        .values()
+        .find(|i| {
+            let target = match i.op_code {
+                1 | 2 | 7 | 8 => i.params[2],
+                3 => i.params[0],
+                _ => return false,
+            };
+            target.0 == 0 && usize::try_from(target.1).is_ok_and(|t| code.contains(&t))
+        })
+        .map(|i| i.address)
+}
+
+fn interpreted(program: &[i64], name: &str, address: usize) -> String {
+    format!(
+        "/// Instruction {} writes into the program, so this runs on the interpreter.
+pub fn {}(input: impl IntoIterator<Item = i64>) -> Vec<i64> {{
+    let mut computer = crate::intcode::IntcodeComputer::new(&{});
+    input.into_iter().for_each(|i| computer.push_input(i));
+    computer.run();
+    computer.take_outputs()
+}}
+",
+        address,
+        name,
+        memory_literal(program)
+    )
+}
+
+fn native(program: &[i64], name: &str, instructions: &BTreeMap<usize, Instruction>) -> String {
+    let code = code_cells(instructions);
+    let last = code.iter().next_back().map_or(0, |c| c + 1);
+    let code_map: Vec<&str> = (0..last)
+        .map(|c| if code.contains(&c) { "true" } else { "false" })
+        .collect();
+
+    let mut source = format!(
+        "// helpers and state the program never touches are generated anyway
+#[allow(unused)]
+pub fn {}(input: impl IntoIterator<Item = i64>) -> Vec<i64> {{
+    const CODE: [bool; {}] = [{}];
+
+    fn address(value: i64) -> usize {{
+        usize::try_from(value).unwrap_or_else(|_| panic!(\"Address {{}} is negative!\", value))
+    }}
+
+    fn read(memory: &[i64], value: i64) -> i64 {{
+        *memory.get(address(value)).unwrap_or(&0)
+    }}
+
+    /// Returns whether the write changed the code.
+    fn write(memory: &mut Vec<i64>, value: i64, result: i64) -> bool {{
+        let address = address(value);
+        if address >= memory.len() {{
+            memory.resize(address + 1, 0);
+        }}
+        memory[address] = result;
+        CODE.get(address) == Some(&true)
+    }}
+
+    fn interpret(
+        memory: &[i64],
+        ip: usize,
+        relative_base: i64,
+        input: impl Iterator<Item = i64>,
+        mut outputs: Vec<i64>,
+    ) -> Vec<i64> {{
+        let mut computer = crate::intcode::IntcodeComputer::resume(memory, ip, relative_base);
+        input.for_each(|i| computer.push_input(i));
+        computer.run();
+        outputs.extend(computer.take_outputs());
+        outputs
+    }}
+
+    let mut memory: Vec<i64> = {}.to_vec();
+    let mut input = input.into_iter();
+    let mut outputs = vec![];
+    let mut relative_base: i64 = 0;
+    let mut ip: usize = 0;
+
+    loop {{
+        match ip {{
+",
+        name,
+        last,
+        code_map.join(", "),
+        memory_literal(program)
+    );
+
+    for instruction in instructions.values() {
+        source += &format!(
+            "            {} => {{\n{}            }}\n",
+            instruction.address,
+            body(instruction)
+        );
+    }
+
+    source += "            _ => return interpret(&memory, ip, relative_base, input, outputs),
+        }
+    }
+}
+";
+
+    source
+}
+
+fn body(instruction: &Instruction) -> String {
+    let next = instruction.address + instruction.len();
+    let param = |n: usize| operand(instruction.params[n]);
+    let target = |n: usize| match instruction.params[n] {
+        (2, value) => format!("relative_base + {}_i64", value),
+        (_, value) => format!("{}_i64", value),
+    };
+    let write = |n: usize| {
+        format!(
+            "if write(&mut memory, {}, value) {{ return interpret(&memory, {}, relative_base, input, outputs); }}",
+            target(n),
+            next
+        )
+    };
+
+    let lines = match instruction.op_code {
+        1 | 2 | 7 | 8 => {
+            let value = match instruction.op_code {
+                1 => format!("{} + {}", param(0), param(1)),
+                2 => format!("{} * {}", param(0), param(1)),
+                7 => format!("i64::from({} < {})", param(0), param(1)),
+                _ => format!("i64::from({} == {})", param(0), param(1)),
+            };
+            vec![
+                format!("let value = {};", value),
+                write(2),
+                format!("ip = {};", next),
+            ]
+        }
+        3 => vec![
+            String::from("let Some(value) = input.next() else { return outputs };"),
+            write(0),
+            format!("ip = {};", next),
+        ],
+        4 => vec![
+            format!("outputs.push({});", param(0)),
+            format!("ip = {};", next),
+        ],
+        5 | 6 => vec![format!(
+            "ip = if {} {} 0 {{ address({}) }} else {{ {} }};",
+            param(0),
+            if instruction.op_code == 5 { "!=" } else { "==" },
+            param(1),
+            next
+        )],
+        9 => vec![
+            format!("relative_base += {};", param(0)),
+            format!("ip = {};", next),
+        ],
+        _ => vec![String::from("return outputs;")],
+    };
+
+    lines
+        .iter()
+        .map(|line| format!("                {}\n", line))
+        .collect()
+}
+
+fn operand((mode, value): (i64, i64)) -> String {
+    match mode {
+        0 => format!("read(&memory, {}_i64)", value),
+        1 => format!("{}_i64", value),
+        _ => format!("read(&memory, relative_base + {}_i64)", value),
+    }
+}
+
+fn memory_literal(program: &[i64]) -> String {
+    let values: Vec<String> = program.iter().map(|v| format!("{}_i64", v)).collect();
+
+    format!("[{}]", values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process::Command};
+
+    use super::*;
+    use crate::intcode::{parse, IntcodeComputer};
+
+    const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+    fn interpret(program: &[i64], inputs: &[i64]) -> Vec<i64> {
+        let mut computer = IntcodeComputer::new(program);
+        inputs.iter().for_each(|i| computer.push_input(*i));
+        computer.run();
+        computer.take_outputs()
+    }
+
+    /// Compiles the programs into one binary that prints the outputs of every
+    /// program, one line each.
+    fn compile_and_run(programs: &[(&[i64], &[i64])]) -> Vec<Vec<i64>> {
+        let directory = env::temp_dir().join(format!("intcode-transpile-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        // the generated code hands over to this crate's interpreter
+        let mut source = format!(
+            "#[path = \"{}/src\"]\nmod source {{\n    #[allow(dead_code)]\n    pub mod intcode;\n}}\nuse source::intcode;\n",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut main = String::from("fn main() {\n");
+        for (n, (program, inputs)) in programs.iter().enumerate() {
+            source += &transpile(program, &format!("program_{}", n));
+            main += &format!("    println!(\"{{:?}}\", program_{}({:?}));\n", n, inputs);
+        }
+        source += &(main + "}\n");
+
+        let file = directory.join("programs.rs");
+        let binary = directory.join("programs");
+        fs::write(&file, source).unwrap();
+
+        let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "-O", "-o"])
+            .arg(&binary)
+            .arg(&file)
+            .status()
+            .unwrap();
+        assert!(status.success(), "Generated code did not compile!");
+
+        let output = Command::new(&binary).output().unwrap();
+        fs::remove_dir_all(directory).unwrap();
+
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let values = line.trim_matches(|c| c == '[' || c == ']');
+                if values.is_empty() {
+                    vec![]
+                } else {
+                    parse(&values.replace(' ', ""))
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_detect_self_modification() {
+        let program = parse("1002,4,3,4,33");
+
+        assert_eq!(self_modification(&reachable(&program)), Some(0));
+        assert!(transpile(&program, "run").contains("IntcodeComputer::new"));
+
+        let program = parse("3,9,8,9,10,9,4,9,99,-1,8");
+
+        assert_eq!(self_modification(&reachable(&program)), None);
+        assert!(!transpile(&program, "run").contains("IntcodeComputer::new"));
+    }
+
+    #[test]
+    fn should_compile_day9_examples() {
+        let quine = parse(QUINE);
+        let product = parse("1102,34915192,34915192,7,4,7,99,0");
+        let large = parse("104,1125899906842624,99");
+        let compare = parse("3,9,8,9,10,9,4,9,99,-1,8");
+
+        let programs: [(&[i64], &[i64]); 5] = [
+            (&quine, &[]),
+            (&product, &[]),
+            (&large, &[]),
+            (&compare, &[8]),
+            (&compare, &[]),
+        ];
+
+        let expected: Vec<Vec<i64>> = programs
+            .iter()
+            .map(|(program, inputs)| interpret(program, inputs))
+            .collect();
+
+        assert_eq!(compile_and_run(&programs), expected);
+    }
+
+    #[test]
+    fn should_compile_boost() {
+        let program = parse(&fs::read_to_string("src/day9.txt").unwrap());
+
+        let programs: [(&[i64], &[i64]); 2] = [(&program, &[1]), (&program, &[2])];
+
+        assert_eq!(
+            compile_and_run(&programs),
+            vec![interpret(&program, &[1]), interpret(&program, &[2])]
+        );
+    }
+
+    #[test]
+    fn should_compile_returns_after_immediate_jumps() {
+        // calls the output of 1 at 12, which returns to the output of 7 at 9
+        let program = parse("109,100,21101,9,0,0,1105,1,12,104,7,99,104,1,2106,0,0");
+
+        assert_eq!(compile_and_run(&[(&program, &[])]), vec![vec![1, 7]]);
+    }
+
+    #[test]
+    fn should_hand_over_relative_writes_into_code() {
+        // jumps over data at 3 and writes 5 there through the relative base
+        let data = parse("1105,1,4,0,109,3,21101,5,0,0,204,0,99");
+        let parameter = parse("109,1,203,2,204,2,99");
+        let stack = parse("109,1,203,11,209,8,204,1,99,10,0,42,0");
+
+        assert!(!transpile(&data, "run").contains("IntcodeComputer::new"));
+
+        let programs: [(&[i64], &[i64]); 3] = [(&data, &[]), (&parameter, &[1]), (&stack, &[1])];
+
+        assert_eq!(compile_and_run(&programs), vec![vec![5], vec![1], vec![1]]);
+    }
+
+    #[test]
+    fn should_hand_over_jumps_past_decoded_code() {
+        // jumps to 6 through the address at 10, the static scan stops at 3
+        let program = parse("105,1,10,99,0,0,104,5,99,0,6");
+
+        assert_eq!(compile_and_run(&[(&program, &[])]), vec![vec![5]]);
+    }
+}
@@ -1,5 +1,13 @@
 mod day1;
+mod day11;
+mod day13;
+mod day15;
+mod day17;
+mod day19;
 mod day2;
+mod day21;
+mod day23;
+mod day25;
 mod day3;
 mod day4;
 mod day5;
@@ -8,7 +16,17 @@ mod day7;
 mod day8;
 mod day9;
 
+#[cfg(test)]
+mod fuzz;
+
+mod intcode;
+mod tools;
+
 fn main() {
+    if tools::run() {
+        return;
+    }
+
     day1::solve();
     day2::solve();
     day3::solve();
@@ -18,4 +36,12 @@ fn main() {
     day7::solve();
     day8::solve();
     day9::solve();
+    day11::solve();
+    day13::solve();
+    day15::solve();
+    day17::solve();
+    day19::solve();
+    day21::solve();
+    day23::solve();
+    day25::solve();
 }
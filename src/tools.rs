@@ -0,0 +1,99 @@
+//! Intcode tools that work on any program file instead of a puzzle input. Each
+//! one runs instead of the puzzles when its environment variable is set.
+
+use std::{
+    env, fs,
+    io::{stdin, stdout},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::intcode::{
+    self, binary, device::Device, disassembly, dump, optimize, transpile, IntcodeComputer,
+};
+
+/// Extension of programs in the binary format.
+const BINARY: &str = "icb";
+
+/// Set this environment variable to a text program to convert it to the binary
+/// format, into a file next to it with the `icb` extension.
+const CONVERT: &str = "CONVERT_INTCODE";
+
+/// Set this environment variable to a program to print its disassembly.
+const DISASSEMBLE: &str = "DISASSEMBLE_INTCODE";
+
+/// Set this environment variable to a program to print it optimized, followed
+/// by the cells the optimizer changed.
+const OPTIMIZE: &str = "OPTIMIZE_INTCODE";
+
+/// Set this environment variable to a program to print it as Rust source.
+const TRANSPILE: &str = "TRANSPILE_INTCODE";
+
+/// Set this environment variable to a program to run it in the terminal, it
+/// talks in ASCII like the puzzles that expect a player.
+const RUN: &str = "RUN_INTCODE";
+
+/// Set this environment variable to an address to map a clock there while a
+/// program runs in the terminal.
+const CLOCK: &str = "INTCODE_CLOCK";
+
+/// Runs the tool whose environment variable is set, returns `false` if there
+/// is none.
+pub fn run() -> bool {
+    if let Ok(path) = env::var(CONVERT) {
+        let binary_path = Path::new(&path).with_extension(BINARY);
+        binary::convert(&path, &binary_path).unwrap();
+        println!("Converted {} to {}", path, binary_path.display());
+    } else if let Ok(path) = env::var(DISASSEMBLE) {
+        for (address, line) in disassembly::disassemble(&read(&path)) {
+            println!("{:>5}: {}", address, line);
+        }
+    } else if let Ok(path) = env::var(OPTIMIZE) {
+        let program = read(&path);
+        let optimized = optimize::optimize(&program);
+        let values: Vec<String> = optimized.iter().map(|v| v.to_string()).collect();
+
+        println!("{}", values.join(","));
+        print!("{}", dump::report(&program, &optimized));
+    } else if let Ok(path) = env::var(TRANSPILE) {
+        print!("{}", transpile::transpile(&read(&path), "run"));
+    } else if let Ok(path) = env::var(RUN) {
+        let mut computer = IntcodeComputer::new(&read(&path));
+        if let Ok(address) = env::var(CLOCK) {
+            let address: usize = address
+                .parse()
+                .unwrap_or_else(|_| panic!("Clock address {} is not a number!", address));
+            let clock = Arc::new(Mutex::new(Clock(Instant::now())));
+            computer.map_device(address..address + 1, clock);
+        }
+
+        for value in computer.run_interactive(stdin().lock(), stdout()) {
+            println!("{}", value);
+        }
+    } else {
+        return false;
+    }
+
+    true
+}
+
+/// Reads a program in the text format, or the binary one by its extension.
+fn read(path: &str) -> Vec<i64> {
+    if Path::new(path).extension().is_some_and(|e| e == BINARY) {
+        binary::load(path).unwrap()
+    } else {
+        intcode::parse(&fs::read_to_string(path).unwrap())
+    }
+}
+
+/// Reads as the milliseconds since the program started, writes are ignored.
+struct Clock(Instant);
+
+impl Device for Clock {
+    fn read(&mut self, _: usize) -> i64 {
+        self.0.elapsed().as_millis() as i64
+    }
+
+    fn write(&mut self, _: usize, _: i64) {}
+}